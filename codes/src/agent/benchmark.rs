@@ -0,0 +1,79 @@
+use crate::agent::dqn::{DqnAgent, DqnConfig};
+use crate::grid::grid_world::{GameStatus, GridWorld};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// how a dqn configuration did over several seeds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Benchmark {
+    // episode returns while training, averaged over the seeds
+    pub returns: Vec<f64>,
+    // share of greedy test games the trained agents won
+    pub win_rate: f64,
+}
+
+// train a fresh agent on a 4x4 world in `mode` for every seed,
+// then play `test_games` greedy games with each; every
+// configuration run on the same seeds sees the same initial
+// weights, boards and exploration draws
+pub fn benchmark_dqn(
+    config: &DqnConfig,
+    mode: &str,
+    seeds: &[u64],
+    epochs: usize,
+    test_games: usize,
+) -> Benchmark {
+    let mut returns = vec![0.0; epochs];
+    let mut wins = 0;
+    for &seed in seeds {
+        tch::manual_seed(seed as i64);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut agent = DqnAgent::with_seed(config.clone(), rng.gen());
+
+        let mut game = GridWorld::with_seed(4, String::from(mode), rng.gen());
+        game.max_moves = Some(50);
+        let log = agent.train(&mut game, epochs);
+        for (total, episode_return) in returns.iter_mut().zip(log.returns.iter()) {
+            *total += episode_return / seeds.len() as f64;
+        }
+
+        for _ in 0..test_games {
+            let mut game = GridWorld::with_seed(4, String::from(mode), rng.gen());
+            game.max_moves = Some(15);
+            if agent.play(&mut game, false) == GameStatus::Won {
+                wins += 1;
+            }
+        }
+    }
+    Benchmark {
+        returns,
+        win_rate: wins as f64 / (test_games * seeds.len()).max(1) as f64,
+    }
+}
+
+// (index, mean of the last `window` values) at every point,
+// ready for `xy_line_plot`
+pub fn moving_average(values: &[f64], window: usize) -> Vec<(f64, f64)> {
+    (0..values.len())
+        .map(|i| {
+            let start = (i + 1).saturating_sub(window.max(1));
+            let slice = &values[start..=i];
+            (i as f64, slice.iter().sum::<f64>() / slice.len() as f64)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agent::benchmark::*;
+
+    #[test]
+    fn test_moving_average() {
+        let smoothed = moving_average(&[1.0, 3.0, 5.0, 7.0], 2);
+        assert_eq!(
+            smoothed,
+            vec![(0.0, 1.0), (1.0, 2.0), (2.0, 4.0), (3.0, 6.0)]
+        );
+        assert!(moving_average(&[], 10).is_empty());
+    }
+}
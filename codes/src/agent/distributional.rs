@@ -0,0 +1,147 @@
+// fixed atoms z_i = v_min + i * (v_max - v_min) / (atoms - 1)
+// a categorical return distribution puts its probability on,
+// after Bellemare et al., "A Distributional Perspective on
+// Reinforcement Learning" (C51)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Support {
+    pub v_min: f64,
+    pub v_max: f64,
+    pub atoms: usize,
+}
+
+impl Default for Support {
+    // fits GridWorld's discounted returns at gamma 0.9: the -1 of
+    // every move adds up to at most -1 / (1 - 0.9) = -10, a pit
+    // ends on exactly -10 and the goal is worth 10; returns of
+    // larger gammas can fall below v_min and are clipped to it
+    fn default() -> Support {
+        Support {
+            v_min: -10.0,
+            v_max: 10.0,
+            atoms: 51,
+        }
+    }
+}
+
+impl Support {
+    pub fn delta(&self) -> f64 {
+        assert!(self.atoms >= 2, "a support needs at least 2 atoms");
+        (self.v_max - self.v_min) / (self.atoms - 1) as f64
+    }
+
+    pub fn atom(&self, i: usize) -> f64 {
+        self.v_min + i as f64 * self.delta()
+    }
+
+    pub fn values(&self) -> Vec<f64> {
+        (0..self.atoms).map(|i| self.atom(i)).collect()
+    }
+
+    // expected return of a distribution over the atoms
+    pub fn mean(&self, probabilities: &[f64]) -> f64 {
+        probabilities
+            .iter()
+            .enumerate()
+            .map(|(i, p)| p * self.atom(i))
+            .sum()
+    }
+
+    // the distribution of reward + discount * Z, where Z follows
+    // `probabilities`, projected back onto the atoms by splitting
+    // each shifted atom between its two nearest neighbours;
+    // once the episode is done only the reward is left; values
+    // outside the support are clipped to its end atoms
+    pub fn project(
+        &self,
+        probabilities: &[f64],
+        reward: f64,
+        discount: f64,
+        done: bool,
+    ) -> Vec<f64> {
+        let mut projected = vec![0.0; self.atoms];
+        let delta = self.delta();
+        for (i, &p) in probabilities.iter().enumerate() {
+            let z = if done {
+                reward
+            } else {
+                reward + discount * self.atom(i)
+            };
+            let b = (z.max(self.v_min).min(self.v_max) - self.v_min) / delta;
+            // rounding can put b a hair past the last atom
+            let last = self.atoms - 1;
+            let (lower, upper) = (
+                (b.floor() as usize).min(last),
+                (b.ceil() as usize).min(last),
+            );
+            if lower == upper {
+                projected[lower] += p;
+            } else {
+                projected[lower] += p * (upper as f64 - b);
+                projected[upper] += p * (b - lower as f64);
+            }
+        }
+        projected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agent::distributional::*;
+
+    #[test]
+    fn test_support() {
+        let support = Support::default();
+        assert_eq!(support.delta(), 0.4);
+        assert_eq!(support.atom(0), -10.0);
+        assert!((support.atom(50) - 10.0).abs() < 1e-12);
+        assert_eq!(support.values().len(), 51);
+    }
+
+    #[test]
+    fn test_projection() {
+        let support = Support {
+            v_min: -2.0,
+            v_max: 2.0,
+            atoms: 5,
+        };
+        // all mass on 1: -1 + 0.5 * 1 = -0.5 lies halfway between -1 and 0
+        let projected = support.project(&[0.0, 0.0, 0.0, 1.0, 0.0], -1.0, 0.5, false);
+        assert_eq!(projected, vec![0.0, 0.5, 0.5, 0.0, 0.0]);
+
+        // shifted atoms beyond the support are clipped onto its ends
+        let projected = support.project(&[0.5, 0.0, 0.0, 0.0, 0.5], 1.5, 1.0, false);
+        assert_eq!(projected, vec![0.0, 0.25, 0.25, 0.0, 0.5]);
+        assert!((support.mean(&projected) - 0.75).abs() < 1e-12);
+
+        // a finished episode collapses onto the reward
+        let projected = support.project(&[0.2, 0.2, 0.2, 0.2, 0.2], 2.0, 0.9, true);
+        assert_eq!(projected, vec![0.0, 0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_projection_onto_uneven_atoms() {
+        // 49 steps don't divide [-1, 1] exactly, so v_max
+        // lands just past the last atom
+        let support = Support {
+            v_min: -1.0,
+            v_max: 1.0,
+            atoms: 50,
+        };
+        let uniform = vec![1.0 / 50.0; 50];
+        let projected = support.project(&uniform, 1.0, 0.9, true);
+        assert!((projected[49] - 1.0).abs() < 1e-12);
+        let projected = support.project(&uniform, 5.0, 1.0, false);
+        assert!((projected.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!((projected[49] - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 2 atoms")]
+    fn test_single_atom() {
+        let support = Support {
+            atoms: 1,
+            ..Support::default()
+        };
+        support.delta();
+    }
+}
@@ -0,0 +1,772 @@
+use crate::agent::distributional::Support;
+use crate::agent::exploration::{Exploration, ExplorationPolicy, Schedule};
+use crate::agent::n_step::NStepAccumulator;
+use crate::agent::prioritized_replay::{PrioritizedConfig, PrioritizedReplay};
+use crate::agent::q_network::{q_network, QNetwork};
+use crate::agent::replay_buffer::{ReplayBatch, ReplayBuffer};
+use crate::env::environment::Environment;
+use crate::grid::grid_world::{Action, GameStatus, GridWorld};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tch::{nn, nn::Module, nn::OptimizerConfig, Device, Kind, Tensor};
+
+// how the target network follows the online network
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetUpdate {
+    // copy the weights every `sync_frequency` steps
+    Hard,
+    // move the weights a `tau` fraction of the way after every
+    // step: target = tau * online + (1 - tau) * target
+    Soft { tau: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DqnConfig {
+    // network sizes: input -> hidden1 -> hidden2 -> output
+    pub input: i64,
+    pub hidden1: i64,
+    pub hidden2: i64,
+    // one q value per action
+    pub output: i64,
+    // split the network after hidden2 into state value and
+    // action advantage streams, see `DuelingNetwork`
+    pub dueling: bool,
+    // replace the linear layers with noisy ones whose noise starts
+    // at this sigma0 (0.5 is usual); exploration then comes from
+    // the noise, drawn again every step, instead of epsilon-greedy
+    pub noisy: Option<f64>,
+    pub gamma: f64,
+    // learn from the discounted rewards of up to this many steps
+    // before bootstrapping, so goal rewards spread faster
+    pub n_step: usize,
+    pub learning_rate: f64,
+    // uniform noise added to every observation
+    pub noise: f64,
+    // steps after which a training episode is cut short, for
+    // environments like the bandits that never end on their own
+    pub max_steps: usize,
+    // how actions are picked while training; schedules
+    // advance once per epoch, so the default, which reaches
+    // epsilon 0.1 after 900 epochs, suits runs of about 1000
+    // epochs and needs its steps scaled for other lengths
+    pub exploration: Exploration,
+    // learn from minibatches of past transitions instead of
+    // from each transition as it happens
+    pub replay: bool,
+    pub memory_size: usize,
+    pub batch_size: usize,
+    // whether a minibatch may hold the same transition twice
+    pub replacement: bool,
+    // replay transitions with large td errors more often,
+    // weighting their updates to undo the bias
+    pub prioritized: Option<PrioritizedConfig>,
+    // bootstrap from a separate target network, updated
+    // as `target_update` says
+    pub target_network: bool,
+    pub target_update: TargetUpdate,
+    // steps between hard updates
+    pub sync_frequency: usize,
+    // let the online network pick the next action and the target
+    // network value it, instead of taking the target's max,
+    // which overestimates (van Hasselt et al., "Double DQN");
+    // needs `target_network`
+    pub double_dqn: bool,
+    // learn a categorical return distribution over these atoms
+    // for every action instead of its mean (C51), see `Support`
+    pub distributional: Option<Support>,
+}
+
+impl Default for DqnConfig {
+    fn default() -> DqnConfig {
+        DqnConfig {
+            input: 64,
+            hidden1: 150,
+            hidden2: 100,
+            output: 4,
+            dueling: false,
+            noisy: None,
+            gamma: 0.9,
+            n_step: 1,
+            learning_rate: 0.001,
+            noise: 0.1,
+            max_steps: 1000,
+            exploration: Exploration::EpsilonGreedy(Schedule::Linear {
+                start: 1.0,
+                end: 0.1,
+                steps: 900,
+            }),
+            replay: false,
+            memory_size: 1000,
+            batch_size: 200,
+            replacement: true,
+            prioritized: None,
+            target_network: false,
+            target_update: TargetUpdate::Hard,
+            sync_frequency: 500,
+            double_dqn: false,
+            distributional: None,
+        }
+    }
+}
+
+// what happened while training
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrainingLog {
+    // loss of every update
+    pub losses: Vec<f64>,
+    // undiscounted sum of rewards of every episode
+    pub returns: Vec<f64>,
+}
+
+// deep q-learning agent, optionally with experience replay
+// and a target network
+pub struct DqnAgent {
+    pub config: DqnConfig,
+    pub vs: nn::VarStore,
+    model: QNetwork,
+    // its own weights, following `vs` by `config.target_update`
+    target: Option<(nn::VarStore, QNetwork)>,
+    optimizer: nn::Optimizer<nn::Adam>,
+    // without replay it only holds the latest transition
+    replay: ReplayBuffer,
+    priorities: Option<PrioritizedReplay>,
+    // turns steps into n-step transitions for `replay`
+    accumulator: NStepAccumulator,
+    pub exploration: Box<dyn ExplorationPolicy>,
+    // environment steps taken while training
+    steps: usize,
+    // source of exploration and minibatch sampling
+    rng: StdRng,
+}
+
+impl DqnAgent {
+    pub fn new(config: DqnConfig) -> DqnAgent {
+        DqnAgent::with_rng(config, StdRng::from_entropy())
+    }
+
+    // network weights come from tch's own generator,
+    // see `tch::manual_seed`
+    pub fn with_seed(config: DqnConfig, seed: u64) -> DqnAgent {
+        DqnAgent::with_rng(config, StdRng::seed_from_u64(seed))
+    }
+
+    pub fn with_rng(config: DqnConfig, rng: StdRng) -> DqnAgent {
+        assert!(
+            !config.double_dqn || config.target_network,
+            "double_dqn needs a target network to value the next action"
+        );
+        if let TargetUpdate::Soft { tau } = config.target_update {
+            assert!(
+                tau > 0.0 && tau <= 1.0,
+                "soft update tau must be in (0, 1], got {}",
+                tau
+            );
+        }
+        let vs = nn::VarStore::new(Device::Cpu);
+        let model = q_network(&vs.root(), &config);
+        let target = if config.target_network {
+            let mut target_vs = nn::VarStore::new(Device::Cpu);
+            let target_model = q_network(&target_vs.root(), &config);
+            target_vs.copy(&vs).unwrap();
+            target_vs.freeze();
+            Some((target_vs, target_model))
+        } else {
+            None
+        };
+        let optimizer = nn::Adam::default()
+            .build(&vs, config.learning_rate)
+            .unwrap();
+        let memory_size = if config.replay { config.memory_size } else { 1 };
+        let priorities = match config.prioritized {
+            Some(prioritized) if config.replay => {
+                Some(PrioritizedReplay::new(memory_size, prioritized))
+            }
+            _ => None,
+        };
+        DqnAgent {
+            replay: ReplayBuffer::new(memory_size, config.input as usize),
+            priorities,
+            accumulator: NStepAccumulator::new(config.n_step, config.gamma),
+            exploration: config.exploration.policy(),
+            config,
+            vs,
+            model,
+            target,
+            optimizer,
+            steps: 0,
+            rng,
+        }
+    }
+
+    // observation with a small amount of uniform noise added
+    pub fn observe(&mut self, observation: &[f64]) -> Vec<f32> {
+        let noise = self.config.noise;
+        observation
+            .iter()
+            .map(|&x| (x + self.rng.gen_range(0.0..1.0) * noise) as f32)
+            .collect()
+    }
+
+    // observation as a [1, input] tensor
+    pub fn state_tensor(state: &[f32]) -> Tensor {
+        Tensor::of_slice(state).unsqueeze(0)
+    }
+
+    // noisy networks use the noise last drawn, see `eval`
+    pub fn q_values(&self, state: &Tensor) -> Tensor {
+        tch::no_grad(|| self.q_of(&self.model, state))
+    }
+
+    // q values [batch_size, actions] of a network, the mean
+    // of its return distributions for C51
+    fn q_of(&self, model: &dyn Module, states: &Tensor) -> Tensor {
+        match &self.config.distributional {
+            None => model.forward(states),
+            Some(support) => {
+                let atoms = Tensor::of_slice(&support.values()).to_kind(Kind::Float);
+                (self.distribution_of(model, states) * atoms).sum_dim_intlist(
+                    &[2],
+                    false,
+                    Kind::Float,
+                )
+            }
+        }
+    }
+
+    // return distributions [batch_size, actions, atoms] of a C51 network
+    fn distribution_of(&self, model: &dyn Module, states: &Tensor) -> Tensor {
+        let atoms = self.config.distributional.expect("not a C51 agent").atoms as i64;
+        model
+            .forward(states)
+            .view([-1, self.config.output, atoms])
+            .softmax(2, Kind::Float)
+    }
+
+    // probability of every atom of `support` for every action
+    // in `state`, with `distributional` set
+    pub fn return_distribution(&self, state: &Tensor) -> Vec<Vec<f64>> {
+        let distribution = tch::no_grad(|| self.distribution_of(&self.model, state));
+        Vec::<Vec<f64>>::from(&distribution.squeeze_dim(0))
+    }
+
+    pub fn greedy_action(&self, state: &Tensor) -> usize {
+        i64::from(self.q_values(state).argmax(1, true)) as usize
+    }
+
+    // action picked by the exploration policy, or greedy
+    // under fresh noise for noisy networks; policies see the q
+    // values, so there is a forward pass even when epsilon-greedy
+    // then picks at random, which is cheap for these small networks
+    pub fn act(&mut self, state: &Tensor) -> usize {
+        if self.config.noisy.is_some() {
+            self.model.reset_noise();
+            return self.greedy_action(state);
+        }
+        let values = Vec::<f64>::from(&self.q_values(state).squeeze_dim(0));
+        self.exploration.select(&values, &mut self.rng)
+    }
+
+    // play `epochs` episodes of at most `max_steps` steps,
+    // learning as we go
+    pub fn train<E: Environment>(&mut self, env: &mut E, epochs: usize) -> TrainingLog {
+        let mut log = TrainingLog::default();
+        for _ in 0..epochs {
+            // for each epoch, we start a new game
+            let observation = env.reset(None);
+            let mut state = self.observe(&observation);
+            let mut episode_return = 0.0;
+            for episode_step in 1.. {
+                let action = self.act(&DqnAgent::state_tensor(&state));
+                let step = env.step(action);
+                let state2 = self.observe(&step.observation);
+                // hitting the cap truncates: the last state still
+                // bootstraps, but the n-step window is flushed
+                let episode_end = step.done() || episode_step >= self.config.max_steps;
+                let completed = self.accumulator.push(
+                    state,
+                    action,
+                    step.reward,
+                    &state2,
+                    step.terminated,
+                    episode_end,
+                );
+                state = state2;
+                episode_return += step.reward;
+                self.steps += 1;
+
+                for transition in completed {
+                    let slot = self.replay.push(
+                        &transition.state,
+                        transition.action,
+                        transition.reward,
+                        &transition.next_state,
+                        transition.done,
+                        transition.discount,
+                    );
+                    if let Some(priorities) = &mut self.priorities {
+                        priorities.insert(slot);
+                    }
+                    // without replay every transition is learned from
+                    // before the next one overwrites it
+                    if !self.config.replay {
+                        log.losses.extend(self.learn());
+                    }
+                }
+                if self.config.replay {
+                    log.losses.extend(self.learn());
+                }
+                match self.config.target_update {
+                    TargetUpdate::Hard => {
+                        if self.steps.is_multiple_of(self.config.sync_frequency) {
+                            self.sync_target();
+                        }
+                    }
+                    TargetUpdate::Soft { tau } => self.soft_update_target(tau),
+                }
+                if episode_end {
+                    break;
+                }
+            }
+            log.returns.push(episode_return);
+
+            self.exploration.step();
+        }
+        log
+    }
+
+    // learn from the latest transition, either directly or
+    // by replaying a minibatch once enough are remembered
+    fn learn(&mut self) -> Option<f64> {
+        if !self.config.replay {
+            let batch = self.replay.batch(vec![self.replay.last()?]);
+            return Some(self.update(batch, None).0);
+        }
+        if self.replay.len() <= self.config.batch_size {
+            return None;
+        }
+
+        match &mut self.priorities {
+            Some(priorities) => {
+                let (slots, weights) = priorities.sample(&mut self.rng, self.config.batch_size);
+                let batch = self.replay.batch(slots);
+                let weights = Tensor::of_slice(&weights).to_kind(Kind::Float);
+                let slots = batch.indices.clone();
+                let (loss, td_errors) = self.update(batch, Some(weights));
+                if let Some(priorities) = &mut self.priorities {
+                    priorities.update_priorities(&slots, &td_errors);
+                }
+                Some(loss)
+            }
+            None => {
+                let batch = self.replay.sample(
+                    &mut self.rng,
+                    self.config.batch_size,
+                    self.config.replacement,
+                );
+                Some(self.update(batch, None).0)
+            }
+        }
+    }
+
+    // one gradient step on a batch, with each squared error
+    // scaled by its importance-sampling weight if given;
+    // returns the loss and the td error of every transition
+    fn update(&mut self, batch: ReplayBatch, weights: Option<Tensor>) -> (f64, Vec<f64>) {
+        if self.config.noisy.is_some() {
+            self.model.reset_noise();
+            if let Some((_, target_model)) = &self.target {
+                target_model.reset_noise();
+            }
+        }
+        if let Some(support) = self.config.distributional {
+            return self.update_distributional(support, batch, weights);
+        }
+        let ReplayBatch {
+            states: state1_batch,
+            actions: action_batch,
+            rewards: reward_batch,
+            next_states: state2_batch,
+            dones: done_batch,
+            discounts: discount_batch,
+            ..
+        } = batch;
+
+        // q1 size: [batch_size, actions], with gradients
+        let q1 = self.model.forward(&state1_batch);
+        // q2 size: [batch_size], value of the next state, without
+        let q2 = tch::no_grad(|| {
+            let next_q = match &self.target {
+                Some((_, target_model)) => target_model.forward(&state2_batch),
+                None => self.model.forward(&state2_batch),
+            };
+            if self.config.double_dqn {
+                let next_action = self.model.forward(&state2_batch).argmax(1, true);
+                next_q.gather(1, &next_action, false).squeeze_dim(1)
+            } else {
+                next_q.max_dim(1, false).0
+            }
+        });
+
+        // q-learning schema:
+        // Q(S_t, A_t) <- R_t+1 + discount * maxQ(S_t+1, a),
+        // or Q'(S_t+1, argmax Q(S_t+1, a)) for double dqn,
+        // without the max once the game has ended; n-step
+        // transitions bring their n-step return and gamma^n
+        let q1_selected = q1
+            .gather(1, &action_batch.unsqueeze(1), false)
+            .squeeze_dim(1);
+        let y_target: Tensor = reward_batch + discount_batch * ((1 - done_batch) * q2);
+
+        let td_errors = &y_target.detach() - &q1_selected;
+        let loss = match weights {
+            Some(weights) => (td_errors.square() * weights).sum(Kind::Float),
+            None => q1_selected.mse_loss(&y_target.detach(), tch::Reduction::Sum),
+        };
+        self.optimizer.zero_grad();
+        self.optimizer.backward_step(&loss);
+        (f64::from(loss), Vec::<f64>::from(&td_errors.detach()))
+    }
+
+    // C51 step: move the predicted distribution of the taken
+    // action towards reward + discount * Z(S_t+1, a*), projected
+    // onto the support, by minimizing their cross-entropy; the
+    // cross-entropies stand in for td errors
+    fn update_distributional(
+        &mut self,
+        support: Support,
+        batch: ReplayBatch,
+        weights: Option<Tensor>,
+    ) -> (f64, Vec<f64>) {
+        let atoms = support.atoms as i64;
+        let batch_size = batch.actions.size()[0];
+
+        // log p size: [batch_size, atoms], with gradients
+        let log_p = self
+            .model
+            .forward(&batch.states)
+            .view([-1, self.config.output, atoms])
+            .log_softmax(2, Kind::Float)
+            .gather(
+                1,
+                &batch
+                    .actions
+                    .view([-1, 1, 1])
+                    .expand(&[-1, 1, atoms], false),
+                false,
+            )
+            .squeeze_dim(1);
+
+        // next size: [batch_size, atoms], distribution of the
+        // best next action, without gradients
+        let next = tch::no_grad(|| {
+            let evaluator: &dyn Module = match &self.target {
+                Some((_, target_model)) => target_model,
+                None => &self.model,
+            };
+            let selector = if self.config.double_dqn {
+                &self.model
+            } else {
+                evaluator
+            };
+            let next_action = self.q_of(selector, &batch.next_states).argmax(1, true);
+            self.distribution_of(evaluator, &batch.next_states)
+                .gather(
+                    1,
+                    &next_action.unsqueeze(2).expand(&[-1, 1, atoms], false),
+                    false,
+                )
+                .squeeze_dim(1)
+        });
+
+        let next = Vec::<Vec<f64>>::from(&next);
+        let rewards = Vec::<f64>::from(&batch.rewards);
+        let dones = Vec::<f64>::from(&batch.dones);
+        let discounts = Vec::<f64>::from(&batch.discounts);
+        let projected: Vec<f64> = (0..next.len())
+            .flat_map(|i| support.project(&next[i], rewards[i], discounts[i], dones[i] > 0.5))
+            .collect();
+        let projected = Tensor::of_slice(&projected)
+            .to_kind(Kind::Float)
+            .view([batch_size, atoms]);
+
+        let cross_entropy = -(projected * &log_p).sum_dim_intlist(&[1], false, Kind::Float);
+        let loss = match weights {
+            Some(weights) => (&cross_entropy * weights).sum(Kind::Float),
+            None => cross_entropy.sum(Kind::Float),
+        };
+        self.optimizer.zero_grad();
+        self.optimizer.backward_step(&loss);
+        (f64::from(loss), Vec::<f64>::from(&cross_entropy.detach()))
+    }
+
+    // copy the online weights into the target network
+    pub fn sync_target(&mut self) {
+        if let Some((target_vs, _)) = &mut self.target {
+            target_vs.copy(&self.vs).unwrap();
+        }
+    }
+
+    // blend the online weights into the target network
+    pub fn soft_update_target(&mut self, tau: f64) {
+        if let Some((target_vs, _)) = &mut self.target {
+            let online = self.vs.variables();
+            tch::no_grad(|| {
+                for (name, mut target) in target_vs.variables() {
+                    let blended = &online[&name] * tau + &target * (1.0 - tau);
+                    target.copy_(&blended);
+                }
+            });
+        }
+    }
+
+    // switch noisy layers to their mean weights, so q values and
+    // greedy actions no longer depend on the last noise drawn,
+    // or back to training with fresh noise
+    pub fn eval(&mut self, evaluating: bool) {
+        self.model.eval(evaluating);
+    }
+
+    // play one greedy game from a fresh board, printing every
+    // move when `display` is set; noisy layers play without
+    // noise and get fresh noise afterwards
+    pub fn play(&mut self, game: &mut GridWorld, display: bool) -> GameStatus {
+        self.eval(true);
+        let observation = game.reset(None);
+        let mut state = self.observe(&observation);
+        if display {
+            println!("Initial State: ");
+            game.display();
+        }
+
+        let mut i = 0;
+        while game.status() == GameStatus::Running {
+            let action = self.greedy_action(&DqnAgent::state_tensor(&state));
+            if display {
+                println!(
+                    "Move #: {}; Taking action: {:?}",
+                    i,
+                    Action::from_index(action)
+                );
+            }
+            let step = game.step(action);
+            state = self.observe(&step.observation);
+            if display {
+                game.display();
+                match game.status() {
+                    GameStatus::Won => println!("Game won! Reward: {}", step.reward),
+                    GameStatus::Lost => println!("Game Lost. Reward: {}", step.reward),
+                    GameStatus::Truncated => println!("Game lost; too many moves."),
+                    GameStatus::Running => {}
+                }
+            }
+            i += 1;
+        }
+        self.eval(false);
+        game.status()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agent::dqn::*;
+    use crate::util::math::argmax;
+    use std::collections::HashMap;
+
+    fn small_config() -> DqnConfig {
+        DqnConfig {
+            input: 3,
+            hidden1: 8,
+            hidden2: 8,
+            output: 2,
+            ..DqnConfig::default()
+        }
+    }
+
+    // two transitions, the second one ending the episode
+    fn batch(agent: &DqnAgent) -> ReplayBatch {
+        let mut buffer = ReplayBuffer::new(2, agent.config.input as usize);
+        buffer.push(&[1.0, 0.0, 0.0], 1, -1.0, &[0.0, 1.0, 0.0], false, 0.9);
+        buffer.push(&[0.0, 1.0, 0.0], 0, 10.0, &[0.0, 0.0, 1.0], true, 0.9);
+        buffer.batch(vec![0, 1])
+    }
+
+    fn q(agent: &DqnAgent, state: &[f32]) -> Vec<f64> {
+        Vec::<f64>::from(
+            &agent
+                .q_values(&DqnAgent::state_tensor(state))
+                .squeeze_dim(0),
+        )
+    }
+
+    // q values of the target network
+    fn target_q(agent: &DqnAgent, state: &[f32]) -> Vec<f64> {
+        let (_, target_model) = agent.target.as_ref().unwrap();
+        let values = tch::no_grad(|| target_model.forward(&DqnAgent::state_tensor(state)));
+        Vec::<f64>::from(&values.squeeze_dim(0))
+    }
+
+    fn max(values: &[f64]) -> f64 {
+        values.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-4, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_update_target() {
+        tch::manual_seed(0);
+        let mut agent = DqnAgent::with_seed(small_config(), 0);
+        let q1 = q(&agent, &[1.0, 0.0, 0.0]);
+        let q2 = q(&agent, &[0.0, 1.0, 0.0]);
+        let q3 = q(&agent, &[0.0, 0.0, 1.0]);
+        // reward + discount * (1 - done) * max q(next) - q(state, action)
+        let expected = vec![-1.0 + 0.9 * max(&q2) - q1[1], 10.0 - q2[0]];
+
+        let transitions = batch(&agent);
+        let (loss, td_errors) = agent.update(transitions, None);
+        assert_close(&td_errors, &expected);
+        assert!((loss - expected.iter().map(|e| e * e).sum::<f64>()).abs() < 1e-3);
+        // the gradient step moved the online network
+        assert_ne!(q(&agent, &[0.0, 0.0, 1.0]), q3);
+    }
+
+    #[test]
+    fn test_learn_without_replay() {
+        tch::manual_seed(0);
+        let mut agent = DqnAgent::with_seed(small_config(), 0);
+        assert_eq!(agent.learn(), None);
+
+        let q1 = q(&agent, &[1.0, 0.0, 0.0]);
+        agent
+            .replay
+            .push(&[1.0, 0.0, 0.0], 0, 10.0, &[0.0; 3], true, 0.9);
+        let loss = agent.learn().unwrap();
+        assert!((loss - (10.0 - q1[0]).powi(2)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_sync_target() {
+        tch::manual_seed(0);
+        let config = DqnConfig {
+            target_network: true,
+            ..small_config()
+        };
+        let mut agent = DqnAgent::with_seed(config, 0);
+        let state = [0.0, 1.0, 0.0];
+        assert_close(&target_q(&agent, &state), &q(&agent, &state));
+
+        // the target is frozen while the online network learns
+        let before = target_q(&agent, &state);
+        let transitions = batch(&agent);
+        agent.update(transitions, None);
+        assert_close(&target_q(&agent, &state), &before);
+        assert_ne!(q(&agent, &state), before);
+
+        agent.sync_target();
+        assert_close(&target_q(&agent, &state), &q(&agent, &state));
+    }
+
+    #[test]
+    fn test_soft_update() {
+        tch::manual_seed(0);
+        let tau = 0.25;
+        let config = DqnConfig {
+            target_network: true,
+            target_update: TargetUpdate::Soft { tau },
+            ..small_config()
+        };
+        let mut agent = DqnAgent::with_seed(config, 0);
+        let transitions = batch(&agent);
+        agent.update(transitions, None);
+
+        let online = agent.vs.variables();
+        // variables() shares storage with the var store, so
+        // keep a copy of the weights before blending
+        let target: HashMap<String, Tensor> = agent
+            .target
+            .as_ref()
+            .unwrap()
+            .0
+            .variables()
+            .into_iter()
+            .map(|(name, weights)| (name, weights.copy()))
+            .collect();
+        agent.soft_update_target(tau);
+        let blended = agent.target.as_ref().unwrap().0.variables();
+        for (name, weights) in blended.iter() {
+            let expected = &online[name] * tau + &target[name] * (1.0 - tau);
+            let difference = f64::from((weights - expected).abs().max());
+            assert!(difference < 1e-6, "{} is not blended", name);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "soft update tau must be in (0, 1]")]
+    fn test_soft_update_tau() {
+        let config = DqnConfig {
+            target_network: true,
+            target_update: TargetUpdate::Soft { tau: 0.0 },
+            ..small_config()
+        };
+        DqnAgent::with_seed(config, 0);
+    }
+
+    #[test]
+    fn test_double_dqn_target() {
+        tch::manual_seed(0);
+        let config = DqnConfig {
+            target_network: true,
+            double_dqn: true,
+            ..small_config()
+        };
+        let mut agent = DqnAgent::with_seed(config, 0);
+        // one step so the online network differs from the target
+        let transitions = batch(&agent);
+        agent.update(transitions, None);
+
+        let q1 = q(&agent, &[1.0, 0.0, 0.0]);
+        let q2 = q(&agent, &[0.0, 1.0, 0.0]);
+        // the online network picks the next action,
+        // the target network values it
+        let next_action = argmax(&q2);
+        let next_value = target_q(&agent, &[0.0, 1.0, 0.0])[next_action];
+        let expected = vec![-1.0 + 0.9 * next_value - q1[1], 10.0 - q2[0]];
+
+        let transitions = batch(&agent);
+        let (_, td_errors) = agent.update(transitions, None);
+        assert_close(&td_errors, &expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "double_dqn needs a target network")]
+    fn test_double_dqn_needs_target() {
+        let config = DqnConfig {
+            double_dqn: true,
+            ..small_config()
+        };
+        DqnAgent::with_seed(config, 0);
+    }
+
+    #[test]
+    fn test_episode_cap() {
+        use crate::bandit::arm::ArmKind;
+        use crate::bandit::context_bandit::ContextBandit;
+
+        tch::manual_seed(0);
+        let config = DqnConfig {
+            output: 3,
+            max_steps: 5,
+            ..small_config()
+        };
+        let mut agent = DqnAgent::with_seed(config, 0);
+        let mut bandit = ContextBandit::new(3, 3, ArmKind::Bernoulli, 0);
+        // a bandit never ends, the cap ends every episode
+        let log = agent.train(&mut bandit, 2);
+        assert_eq!(log.returns.len(), 2);
+        assert_eq!(log.losses.len(), 10);
+    }
+}
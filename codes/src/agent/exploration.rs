@@ -0,0 +1,243 @@
+use crate::util::math::argmax;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::{Rng, RngCore};
+use std::fmt::Debug;
+
+// a parameter that changes as exploration goes on,
+// indexed by how many times the policy was stepped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Schedule {
+    Constant(f64),
+    // moves from start to end in `steps` equal steps, then stays
+    Linear { start: f64, end: f64, steps: usize },
+    // end + (start - end) * decay^t
+    Exponential { start: f64, end: f64, decay: f64 },
+}
+
+impl Schedule {
+    pub fn value(&self, t: usize) -> f64 {
+        match *self {
+            Schedule::Constant(value) => value,
+            Schedule::Linear { start, end, steps } => {
+                let progress = if steps == 0 {
+                    1.0
+                } else {
+                    (t as f64 / steps as f64).min(1.0)
+                };
+                start + (end - start) * progress
+            }
+            Schedule::Exponential { start, end, decay } => {
+                end + (start - end) * decay.powi(t.min(i32::MAX as usize) as i32)
+            }
+        }
+    }
+}
+
+// turns value estimates of every action into a choice of action;
+// shared by the bandit binaries and `DqnAgent`, and none of them
+// accepts an empty slice of values
+pub trait ExplorationPolicy: Debug {
+    // probability of choosing each action given their values
+    fn probabilities(&self, values: &[f64]) -> Vec<f64>;
+
+    fn select(&mut self, values: &[f64], rng: &mut dyn RngCore) -> usize {
+        let weights = self.probabilities(values);
+        WeightedIndex::new(&weights).unwrap().sample(rng)
+    }
+
+    // advance the schedule, e.g. once per episode
+    fn step(&mut self) {}
+}
+
+// every policy needs at least one action to choose from
+fn check_actions(values: &[f64]) {
+    assert!(!values.is_empty(), "no actions to choose from");
+}
+
+// always the best-looking action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Greedy;
+
+impl ExplorationPolicy for Greedy {
+    fn probabilities(&self, values: &[f64]) -> Vec<f64> {
+        check_actions(values);
+        let mut probabilities = vec![0.0; values.len()];
+        probabilities[argmax(values)] = 1.0;
+        probabilities
+    }
+
+    fn select(&mut self, values: &[f64], _rng: &mut dyn RngCore) -> usize {
+        check_actions(values);
+        argmax(values)
+    }
+}
+
+// a uniformly random action with probability epsilon,
+// the best-looking one otherwise
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpsilonGreedy {
+    pub schedule: Schedule,
+    steps: usize,
+}
+
+impl EpsilonGreedy {
+    pub fn new(schedule: Schedule) -> EpsilonGreedy {
+        EpsilonGreedy { schedule, steps: 0 }
+    }
+
+    pub fn epsilon(&self) -> f64 {
+        self.schedule.value(self.steps)
+    }
+}
+
+impl ExplorationPolicy for EpsilonGreedy {
+    fn probabilities(&self, values: &[f64]) -> Vec<f64> {
+        check_actions(values);
+        let epsilon = self.epsilon();
+        let mut probabilities = vec![epsilon / values.len() as f64; values.len()];
+        probabilities[argmax(values)] += 1.0 - epsilon;
+        probabilities
+    }
+
+    fn select(&mut self, values: &[f64], rng: &mut dyn RngCore) -> usize {
+        check_actions(values);
+        if rng.gen_range(0.0..1.0) > self.epsilon() {
+            argmax(values)
+        } else {
+            rng.gen_range(0..values.len())
+        }
+    }
+
+    fn step(&mut self) {
+        self.steps += 1;
+    }
+}
+
+// softmax over values / temperature; high temperatures
+// explore uniformly, low ones approach greedy, which a
+// temperature of 0 or below is
+#[derive(Debug, Clone, PartialEq)]
+pub struct Boltzmann {
+    pub temperature: Schedule,
+    steps: usize,
+}
+
+impl Boltzmann {
+    pub fn new(temperature: Schedule) -> Boltzmann {
+        Boltzmann {
+            temperature,
+            steps: 0,
+        }
+    }
+
+    pub fn temperature(&self) -> f64 {
+        self.temperature.value(self.steps)
+    }
+}
+
+impl ExplorationPolicy for Boltzmann {
+    fn probabilities(&self, values: &[f64]) -> Vec<f64> {
+        check_actions(values);
+        let tau = self.temperature();
+        if tau <= 0.0 {
+            return Greedy.probabilities(values);
+        }
+        // shifting by the max keeps exp from overflowing
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let powed: Vec<f64> = values.iter().map(|x| ((x - max) / tau).exp()).collect();
+        let sum: f64 = powed.iter().sum();
+        powed.iter().map(|x| x / sum).collect()
+    }
+
+    fn step(&mut self) {
+        self.steps += 1;
+    }
+}
+
+// exploration settings, for configs that must stay `Clone`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Exploration {
+    Greedy,
+    EpsilonGreedy(Schedule),
+    Boltzmann(Schedule),
+}
+
+impl Exploration {
+    pub fn policy(&self) -> Box<dyn ExplorationPolicy> {
+        match *self {
+            Exploration::Greedy => Box::new(Greedy),
+            Exploration::EpsilonGreedy(schedule) => Box::new(EpsilonGreedy::new(schedule)),
+            Exploration::Boltzmann(temperature) => Box::new(Boltzmann::new(temperature)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agent::exploration::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_schedules() {
+        let linear = Schedule::Linear {
+            start: 1.0,
+            end: 0.1,
+            steps: 9,
+        };
+        assert_eq!(linear.value(0), 1.0);
+        assert!((linear.value(3) - 0.7).abs() < 1e-12);
+        assert!((linear.value(100) - 0.1).abs() < 1e-12);
+
+        let exponential = Schedule::Exponential {
+            start: 1.0,
+            end: 0.1,
+            decay: 0.5,
+        };
+        assert_eq!(exponential.value(0), 1.0);
+        assert!((exponential.value(2) - 0.325).abs() < 1e-12);
+        assert_eq!(Schedule::Constant(0.2).value(7), 0.2);
+    }
+
+    #[test]
+    fn test_epsilon_greedy() {
+        let mut policy = EpsilonGreedy::new(Schedule::Linear {
+            start: 0.4,
+            end: 0.0,
+            steps: 2,
+        });
+        let values = [1.0, 3.0, 2.0, 3.0];
+        assert_eq!(policy.probabilities(&values), vec![0.1, 0.7, 0.1, 0.1]);
+
+        policy.step();
+        policy.step();
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!((0..20).all(|_| policy.select(&values, &mut rng) == 1));
+    }
+
+    #[test]
+    fn test_boltzmann() {
+        let policy = Boltzmann::new(Schedule::Constant(1.0));
+        let probabilities = policy.probabilities(&[0.0, 2.0f64.ln(), 1000.0]);
+        assert!(probabilities[2] > 0.999);
+        let probabilities = policy.probabilities(&[0.0, 2.0f64.ln()]);
+        assert!((probabilities[1] - 2.0 / 3.0).abs() < 1e-12);
+
+        let cold = Boltzmann::new(Schedule::Constant(1e-3));
+        assert_eq!(cold.probabilities(&[0.0, 1.0]), vec![0.0, 1.0]);
+        assert_eq!(Greedy.probabilities(&[0.0, 1.0]), vec![0.0, 1.0]);
+
+        // a schedule that reaches 0 ends up greedy, not NaN
+        let mut frozen = Boltzmann::new(Schedule::Constant(0.0));
+        assert_eq!(frozen.probabilities(&[1.0, 3.0, 3.0]), vec![0.0, 1.0, 0.0]);
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(frozen.select(&[1.0, 3.0, 3.0], &mut rng), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "no actions to choose from")]
+    fn test_no_actions() {
+        let mut rng = StdRng::seed_from_u64(0);
+        EpsilonGreedy::new(Schedule::Constant(1.0)).select(&[], &mut rng);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod benchmark;
+pub mod distributional;
+pub mod dqn;
+pub mod exploration;
+pub mod n_step;
+pub mod noisy_linear;
+pub mod prioritized_replay;
+pub mod q_network;
+pub mod replay_buffer;
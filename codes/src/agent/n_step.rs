@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+
+// a transition spanning up to n steps
+#[derive(Debug, Clone, PartialEq)]
+pub struct NStepTransition {
+    pub state: Vec<f32>,
+    pub action: usize,
+    // r_1 + gamma * r_2 + ... + gamma^(k-1) * r_k
+    pub reward: f64,
+    // state reached after the k steps
+    pub next_state: Vec<f32>,
+    // the episode terminated within the k steps
+    pub done: bool,
+    // gamma^k, the discount of the next state's value
+    pub discount: f64,
+}
+
+// turns single steps into n-step transitions: every step starts
+// a transition that is complete n steps later, or at the end of
+// the episode, whichever comes first
+#[derive(Debug, Clone)]
+pub struct NStepAccumulator {
+    pub n: usize,
+    pub gamma: f64,
+    // (state, action, reward) of steps whose transition is incomplete
+    pending: VecDeque<(Vec<f32>, usize, f64)>,
+}
+
+impl NStepAccumulator {
+    pub fn new(n: usize, gamma: f64) -> NStepAccumulator {
+        assert!(n > 0, "n-step returns need at least one step");
+        NStepAccumulator {
+            n,
+            gamma,
+            pending: VecDeque::with_capacity(n),
+        }
+    }
+
+    // add a step and return the transitions it completes;
+    // `terminated` means the episode ended in a terminal state,
+    // `episode_end` that it ended for any reason, truncation
+    // included, which completes every pending transition, so
+    // nothing carries over into the next episode
+    pub fn push(
+        &mut self,
+        state: Vec<f32>,
+        action: usize,
+        reward: f64,
+        next_state: &[f32],
+        terminated: bool,
+        episode_end: bool,
+    ) -> Vec<NStepTransition> {
+        self.pending.push_back((state, action, reward));
+        let mut completed = vec![];
+        if self.pending.len() == self.n {
+            completed.push(self.complete(next_state, terminated));
+        }
+        if episode_end || terminated {
+            while !self.pending.is_empty() {
+                completed.push(self.complete(next_state, terminated));
+            }
+        }
+        completed
+    }
+
+    // the transition of the oldest pending step
+    fn complete(&mut self, next_state: &[f32], done: bool) -> NStepTransition {
+        let mut reward = 0.0;
+        let mut discount = 1.0;
+        for &(_, _, r) in self.pending.iter() {
+            reward += discount * r;
+            discount *= self.gamma;
+        }
+        let (state, action, _) = self.pending.pop_front().unwrap();
+        NStepTransition {
+            state,
+            action,
+            reward,
+            next_state: next_state.to_vec(),
+            done,
+            discount,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agent::n_step::*;
+
+    #[test]
+    fn test_n_step_returns() {
+        let mut accumulator = NStepAccumulator::new(3, 0.5);
+        assert!(accumulator
+            .push(vec![0.0], 0, 1.0, &[1.0], false, false)
+            .is_empty());
+        assert!(accumulator
+            .push(vec![1.0], 1, 2.0, &[2.0], false, false)
+            .is_empty());
+
+        let completed = accumulator.push(vec![2.0], 2, 4.0, &[3.0], false, false);
+        assert_eq!(completed.len(), 1);
+        let first = &completed[0];
+        assert_eq!(first.state, vec![0.0]);
+        assert_eq!(first.reward, 1.0 + 0.5 * 2.0 + 0.25 * 4.0);
+        assert_eq!(first.next_state, vec![3.0]);
+        assert_eq!(first.discount, 0.125);
+        assert!(!first.done);
+
+        // reaching the goal completes the rest with shorter spans
+        let completed = accumulator.push(vec![3.0], 3, 8.0, &[4.0], true, true);
+        assert_eq!(completed.len(), 3);
+        assert_eq!(completed[0].reward, 2.0 + 0.5 * 4.0 + 0.25 * 8.0);
+        assert_eq!(completed[1].reward, 4.0 + 0.5 * 8.0);
+        assert_eq!(completed[2].reward, 8.0);
+        assert_eq!(completed[2].discount, 0.5);
+        assert!(completed
+            .iter()
+            .all(|t| t.done && t.next_state == vec![4.0]));
+    }
+
+    #[test]
+    fn test_truncated_episode() {
+        let mut accumulator = NStepAccumulator::new(2, 0.9);
+        accumulator.push(vec![0.0], 0, -1.0, &[1.0], false, false);
+        // the move limit ran out, the last state is still worth bootstrapping
+        let completed = accumulator.push(vec![1.0], 1, -1.0, &[2.0], false, true);
+        assert_eq!(completed.len(), 2);
+        assert!(completed.iter().all(|t| !t.done));
+        assert_eq!(completed[1].discount, 0.9);
+
+        // a single step behaves like plain one-step q-learning
+        let mut accumulator = NStepAccumulator::new(1, 0.9);
+        let completed = accumulator.push(vec![0.0], 2, -1.0, &[1.0], false, false);
+        assert_eq!(completed[0].reward, -1.0);
+        assert_eq!(completed[0].discount, 0.9);
+    }
+}
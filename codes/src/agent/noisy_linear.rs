@@ -0,0 +1,119 @@
+use tch::{nn, nn::Init, nn::Module, Kind, Tensor};
+
+// linear layer with factorised Gaussian noise on its weights,
+// after Fortunato et al., "Noisy Networks for Exploration":
+//     w = w_mu + w_sigma * (f(e_out) f(e_in)^T)
+//     b = b_mu + b_sigma * f(e_out)
+// with f(x) = sign(x) sqrt(|x|) and e ~ N(0, 1); the sigmas are
+// learned, so the network tunes how much it explores
+#[derive(Debug)]
+pub struct NoisyLinear {
+    weight_mu: Tensor,
+    weight_sigma: Tensor,
+    bias_mu: Tensor,
+    bias_sigma: Tensor,
+    // f(e_in) and f(e_out), stored untrained in the var store
+    epsilon_in: Tensor,
+    epsilon_out: Tensor,
+}
+
+// handle on the noise of a `NoisyLinear`, kept by whoever
+// decides when noise is drawn
+#[derive(Debug)]
+pub struct Noise {
+    epsilon_in: Tensor,
+    epsilon_out: Tensor,
+}
+
+impl NoisyLinear {
+    // `sigma0` scales the initial noise, 0.5 in the paper
+    pub fn new(vs: &nn::Path, input: i64, output: i64, sigma0: f64) -> NoisyLinear {
+        let bound = 1.0 / (input as f64).sqrt();
+        let mu = Init::Uniform {
+            lo: -bound,
+            up: bound,
+        };
+        let sigma = Init::Const(sigma0 * bound);
+        let layer = NoisyLinear {
+            weight_mu: vs.var("weight_mu", &[output, input], mu),
+            weight_sigma: vs.var("weight_sigma", &[output, input], sigma),
+            bias_mu: vs.var("bias_mu", &[output], mu),
+            bias_sigma: vs.var("bias_sigma", &[output], sigma),
+            epsilon_in: vs.zeros_no_train("epsilon_in", &[input]),
+            epsilon_out: vs.zeros_no_train("epsilon_out", &[output]),
+        };
+        layer.noise().resample();
+        layer
+    }
+
+    pub fn noise(&self) -> Noise {
+        Noise {
+            epsilon_in: self.epsilon_in.shallow_clone(),
+            epsilon_out: self.epsilon_out.shallow_clone(),
+        }
+    }
+}
+
+impl Module for NoisyLinear {
+    fn forward(&self, xs: &Tensor) -> Tensor {
+        let weight = &self.weight_mu + &self.weight_sigma * self.epsilon_out.ger(&self.epsilon_in);
+        let bias = &self.bias_mu + &self.bias_sigma * &self.epsilon_out;
+        xs.matmul(&weight.tr()) + bias
+    }
+}
+
+// f(e) for e ~ N(0, 1)
+fn scaled_noise(size: i64, like: &Tensor) -> Tensor {
+    let e = Tensor::randn(&[size], (Kind::Float, like.device()));
+    e.sign() * e.abs().sqrt()
+}
+
+impl Noise {
+    // draw new noise for the layer
+    pub fn resample(&self) {
+        let (mut epsilon_in, mut epsilon_out) = (
+            self.epsilon_in.shallow_clone(),
+            self.epsilon_out.shallow_clone(),
+        );
+        tch::no_grad(|| {
+            epsilon_in.copy_(&scaled_noise(epsilon_in.size()[0], &epsilon_in));
+            epsilon_out.copy_(&scaled_noise(epsilon_out.size()[0], &epsilon_out));
+        });
+    }
+
+    // zero the noise, so the layer is deterministic with its mean weights
+    pub fn remove(&self) {
+        let (mut epsilon_in, mut epsilon_out) = (
+            self.epsilon_in.shallow_clone(),
+            self.epsilon_out.shallow_clone(),
+        );
+        tch::no_grad(|| {
+            let _ = epsilon_in.zero_();
+            let _ = epsilon_out.zero_();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agent::noisy_linear::*;
+    use tch::Device;
+
+    #[test]
+    fn test_noise() {
+        tch::manual_seed(0);
+        let vs = nn::VarStore::new(Device::Cpu);
+        let layer = NoisyLinear::new(&vs.root(), 3, 4, 0.5);
+        let xs = Tensor::rand(&[5, 3], (Kind::Float, Device::Cpu));
+
+        // without noise the layer is deterministic
+        let noise = layer.noise();
+        noise.remove();
+        let ys = layer.forward(&xs);
+        assert_eq!(layer.forward(&xs), ys);
+
+        // new noise perturbs the weights
+        noise.resample();
+        assert_ne!(layer.forward(&xs), ys);
+    }
+}
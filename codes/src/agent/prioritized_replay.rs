@@ -0,0 +1,298 @@
+use rand::Rng;
+
+// binary tree whose leaves hold the priority of each slot and
+// whose inner nodes hold the sum of their children, so totals
+// and prefix-sum lookups take O(log capacity)
+#[derive(Debug, Clone)]
+pub struct SumTree {
+    // leaf count, the capacity rounded up to a power of two
+    // so the leaves sit in slot order on the bottom level
+    leaves: usize,
+    // nodes[1] is the root, leaves start at nodes[leaves]
+    nodes: Vec<f64>,
+}
+
+impl SumTree {
+    pub fn new(capacity: usize) -> SumTree {
+        let leaves = capacity.max(1).next_power_of_two();
+        SumTree {
+            leaves,
+            nodes: vec![0.0; 2 * leaves],
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.nodes[1]
+    }
+
+    pub fn get(&self, slot: usize) -> f64 {
+        self.nodes[self.leaves + slot]
+    }
+
+    pub fn set(&mut self, slot: usize, priority: f64) {
+        let mut node = self.leaves + slot;
+        self.nodes[node] = priority;
+        while node > 1 {
+            node /= 2;
+            self.nodes[node] = self.nodes[2 * node] + self.nodes[2 * node + 1];
+        }
+    }
+
+    // the slot whose cumulative priority range holds `value`,
+    // for `value` in [0, total)
+    pub fn find(&self, mut value: f64) -> usize {
+        let mut node = 1;
+        while node < self.leaves {
+            let left = self.nodes[2 * node];
+            if value < left || self.nodes[2 * node + 1] == 0.0 {
+                node *= 2;
+            } else {
+                value -= left;
+                node = 2 * node + 1;
+            }
+        }
+        node - self.leaves
+    }
+}
+
+// how priorities are turned into sampling probabilities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityMode {
+    // P(i) ~ (|td error| + epsilon)^alpha
+    Proportional,
+    // P(i) ~ (1 / rank)^alpha, ranking slots by |td error|
+    RankBased,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrioritizedConfig {
+    pub mode: PriorityMode,
+    // 0 samples uniformly, 1 fully by priority
+    pub alpha: f64,
+    // importance-sampling exponent, annealed up to 1
+    pub beta: f64,
+    // samples over which beta reaches 1
+    pub beta_steps: usize,
+    // keeps transitions with no td error sampleable
+    pub epsilon: f64,
+}
+
+impl Default for PrioritizedConfig {
+    fn default() -> PrioritizedConfig {
+        PrioritizedConfig {
+            mode: PriorityMode::Proportional,
+            alpha: 0.6,
+            beta: 0.4,
+            beta_steps: 10000,
+            epsilon: 1e-6,
+        }
+    }
+}
+
+// sampling priorities for the slots of a `ReplayBuffer`,
+// after Schaul et al., "Prioritized Experience Replay"
+#[derive(Debug, Clone)]
+pub struct PrioritizedReplay {
+    pub config: PrioritizedConfig,
+    // current importance-sampling exponent
+    pub beta: f64,
+    // |td error| + epsilon of every filled slot
+    priorities: Vec<f64>,
+    // sampling weights, p^alpha or (1/rank)^alpha
+    tree: SumTree,
+    // rank-based weights are stale after a priority change
+    ranks_stale: bool,
+    max_priority: f64,
+}
+
+impl PrioritizedReplay {
+    pub fn new(capacity: usize, config: PrioritizedConfig) -> PrioritizedReplay {
+        PrioritizedReplay {
+            beta: config.beta,
+            config,
+            priorities: vec![],
+            tree: SumTree::new(capacity),
+            ranks_stale: false,
+            max_priority: 1.0,
+        }
+    }
+
+    // a new transition went into `slot`; it gets the highest
+    // priority seen so far so it is replayed at least once
+    pub fn insert(&mut self, slot: usize) {
+        if slot >= self.priorities.len() {
+            self.priorities.resize(slot + 1, 0.0);
+        }
+        self.set_priority(slot, self.max_priority);
+    }
+
+    pub fn priority(&self, slot: usize) -> f64 {
+        self.priorities[slot]
+    }
+
+    // new priorities from the td errors of sampled slots;
+    // a NaN or infinite error means the network diverged
+    pub fn update_priorities(&mut self, slots: &[usize], td_errors: &[f64]) {
+        for (&slot, &td_error) in slots.iter().zip(td_errors.iter()) {
+            assert!(
+                td_error.is_finite(),
+                "td error {} of slot {} is not finite",
+                td_error,
+                slot
+            );
+            let priority = td_error.abs() + self.config.epsilon;
+            self.max_priority = self.max_priority.max(priority);
+            self.set_priority(slot, priority);
+        }
+    }
+
+    fn set_priority(&mut self, slot: usize, priority: f64) {
+        self.priorities[slot] = priority;
+        match self.config.mode {
+            PriorityMode::Proportional => self.tree.set(slot, priority.powf(self.config.alpha)),
+            PriorityMode::RankBased => self.ranks_stale = true,
+        }
+    }
+
+    // re-sorts every slot, O(N log N), so the first `sample`
+    // or `probability` after a priority update pays for a sort
+    // of the whole buffer
+    fn update_ranks(&mut self) {
+        let mut slots: Vec<usize> = (0..self.priorities.len()).collect();
+        slots.sort_by(|&a, &b| self.priorities[b].total_cmp(&self.priorities[a]));
+        for (rank, slot) in slots.into_iter().enumerate() {
+            self.tree
+                .set(slot, (1.0 / (rank + 1) as f64).powf(self.config.alpha));
+        }
+        self.ranks_stale = false;
+    }
+
+    // probability of sampling `slot`
+    pub fn probability(&mut self, slot: usize) -> f64 {
+        if self.ranks_stale {
+            self.update_ranks();
+        }
+        self.tree.get(slot) / self.tree.total()
+    }
+
+    // `batch_size` slots drawn by priority, one from each of
+    // `batch_size` equal slices of the total, with importance-
+    // sampling weights (N * P(i))^-beta scaled so the largest
+    // in the batch is 1; beta moves one step closer to 1
+    pub fn sample<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        batch_size: usize,
+    ) -> (Vec<usize>, Vec<f64>) {
+        assert!(
+            !self.priorities.is_empty(),
+            "cannot sample an empty replay buffer"
+        );
+        if self.ranks_stale {
+            self.update_ranks();
+        }
+
+        let total = self.tree.total();
+        let segment = total / batch_size as f64;
+        let slots: Vec<usize> = (0..batch_size)
+            .map(|i| {
+                let value = segment * (i as f64 + rng.gen_range(0.0..1.0));
+                self.tree.find(value.min(total * (1.0 - 1e-12)))
+            })
+            .collect();
+
+        let n = self.priorities.len() as f64;
+        let weights: Vec<f64> = slots
+            .iter()
+            .map(|&slot| (n * self.tree.get(slot) / total).powf(-self.beta))
+            .collect();
+        let max_weight = weights.iter().copied().fold(0.0, f64::max);
+        let weights = weights.iter().map(|w| w / max_weight).collect();
+
+        self.beta = (self.beta + (1.0 - self.config.beta) / self.config.beta_steps as f64).min(1.0);
+        (slots, weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agent::prioritized_replay::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_sum_tree() {
+        let mut tree = SumTree::new(5);
+        for (slot, &p) in [1.0, 2.0, 3.0, 4.0, 0.0].iter().enumerate() {
+            tree.set(slot, p);
+        }
+        assert_eq!(tree.total(), 10.0);
+        assert_eq!(tree.find(0.5), 0);
+        assert_eq!(tree.find(1.0), 1);
+        assert_eq!(tree.find(5.9), 2);
+        assert_eq!(tree.find(9.99), 3);
+
+        tree.set(1, 0.0);
+        assert_eq!(tree.total(), 8.0);
+        assert_eq!(tree.find(1.0), 2);
+    }
+
+    #[test]
+    fn test_proportional_sampling() {
+        let config = PrioritizedConfig {
+            alpha: 1.0,
+            ..PrioritizedConfig::default()
+        };
+        let mut replay = PrioritizedReplay::new(4, config);
+        for slot in 0..4 {
+            replay.insert(slot);
+        }
+        replay.update_priorities(&[0, 1, 2, 3], &[0.0, 0.0, 0.0, 9.0]);
+        assert!(replay.probability(3) > 0.99);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let (slots, weights) = replay.sample(&mut rng, 8);
+        assert!(slots.iter().all(|&slot| slot == 3));
+        assert!(weights.iter().all(|&w| (w - 1.0).abs() < 1e-12));
+        assert!(replay.beta > config.beta);
+    }
+
+    #[test]
+    fn test_rank_based_sampling() {
+        let config = PrioritizedConfig {
+            mode: PriorityMode::RankBased,
+            alpha: 1.0,
+            beta: 1.0,
+            ..PrioritizedConfig::default()
+        };
+        let mut replay = PrioritizedReplay::new(3, config);
+        for slot in 0..3 {
+            replay.insert(slot);
+        }
+        // ranks 3, 1, 2: weights 1/3, 1, 1/2
+        replay.update_priorities(&[0, 1, 2], &[0.1, -50.0, 2.0]);
+        let total = 1.0 / 3.0 + 1.0 + 0.5;
+        assert!((replay.probability(1) - 1.0 / total).abs() < 1e-12);
+        assert!((replay.probability(0) - (1.0 / 3.0) / total).abs() < 1e-12);
+
+        // weights are 1 / P(i) relative to the rarest sampled slot
+        let mut rng = StdRng::seed_from_u64(1);
+        let (slots, weights) = replay.sample(&mut rng, 3);
+        let rarest = slots
+            .iter()
+            .map(|&slot| replay.probability(slot))
+            .fold(f64::INFINITY, f64::min);
+        for (&slot, weight) in slots.iter().zip(weights.iter()) {
+            assert!((weight - rarest / replay.probability(slot)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "is not finite")]
+    fn test_diverged_td_error() {
+        let mut replay = PrioritizedReplay::new(2, PrioritizedConfig::default());
+        replay.insert(0);
+        replay.insert(1);
+        replay.update_priorities(&[0, 1], &[1.0, f64::NAN]);
+    }
+}
@@ -0,0 +1,223 @@
+use crate::agent::dqn::DqnConfig;
+use crate::agent::noisy_linear::{Noise, NoisyLinear};
+use tch::{nn, nn::Module, Kind, Tensor};
+
+// a plain or a noisy linear layer, as the config asks
+#[derive(Debug)]
+enum Layer {
+    Linear(nn::Linear),
+    Noisy(NoisyLinear),
+}
+
+impl Module for Layer {
+    fn forward(&self, xs: &Tensor) -> Tensor {
+        match self {
+            Layer::Linear(layer) => layer.forward(xs),
+            Layer::Noisy(layer) => layer.forward(xs),
+        }
+    }
+}
+
+// noisy layers hand their noise to `noise`
+fn linear(
+    vs: nn::Path,
+    input: i64,
+    output: i64,
+    config: &DqnConfig,
+    noise: &mut Vec<Noise>,
+) -> Layer {
+    match config.noisy {
+        Some(sigma0) => {
+            let layer = NoisyLinear::new(&vs, input, output, sigma0);
+            noise.push(layer.noise());
+            Layer::Noisy(layer)
+        }
+        None => Layer::Linear(nn::linear(vs, input, output, Default::default())),
+    }
+}
+
+// hidden layers shared by both network kinds
+fn body(vs: &nn::Path, config: &DqnConfig, noise: &mut Vec<Noise>) -> nn::Sequential {
+    nn::seq()
+        .add(linear(
+            vs / "layer1",
+            config.input,
+            config.hidden1,
+            config,
+            noise,
+        ))
+        .add_fn(|xs| xs.relu())
+        .add(linear(
+            vs / "layer2",
+            config.hidden1,
+            config.hidden2,
+            config,
+            noise,
+        ))
+        .add_fn(|xs| xs.relu())
+}
+
+// the plain three-layer mlp, with one output per action,
+// or per action and atom for C51
+pub fn mlp(vs: &nn::Path, config: &DqnConfig, noise: &mut Vec<Noise>) -> nn::Sequential {
+    let atoms = config
+        .distributional
+        .map_or(1, |support| support.atoms as i64);
+    body(vs, config, noise).add(linear(
+        vs / "layer3",
+        config.hidden2,
+        config.output * atoms,
+        config,
+        noise,
+    ))
+}
+
+// Q(s, a) = V(s) + A(s, a) - mean_a' A(s, a'), after Wang et al.,
+// "Dueling Network Architectures for Deep Reinforcement Learning"
+#[derive(Debug)]
+pub struct DuelingNetwork {
+    body: nn::Sequential,
+    value: Layer,
+    advantage: Layer,
+}
+
+impl DuelingNetwork {
+    pub fn new(vs: &nn::Path, config: &DqnConfig, noise: &mut Vec<Noise>) -> DuelingNetwork {
+        DuelingNetwork {
+            body: body(vs, config, noise),
+            value: linear(vs / "value", config.hidden2, 1, config, noise),
+            advantage: linear(
+                vs / "advantage",
+                config.hidden2,
+                config.output,
+                config,
+                noise,
+            ),
+        }
+    }
+}
+
+impl Module for DuelingNetwork {
+    fn forward(&self, xs: &Tensor) -> Tensor {
+        let hidden = self.body.forward(xs);
+        // value size: [batch_size, 1], advantage size: [batch_size, actions]
+        let value = self.value.forward(&hidden);
+        let advantage = self.advantage.forward(&hidden);
+        let mean = advantage.mean_dim(&[1], true, Kind::Float);
+        value + advantage - mean
+    }
+}
+
+// the network `config` asks for, with the noise of its noisy layers
+#[derive(Debug)]
+pub struct QNetwork {
+    model: Box<dyn Module>,
+    noise: Vec<Noise>,
+    // noisy layers keep to their mean weights while evaluating
+    evaluating: bool,
+}
+
+impl QNetwork {
+    // draw new noise for every noisy layer, unless evaluating
+    pub fn reset_noise(&self) {
+        if self.evaluating {
+            return;
+        }
+        for noise in self.noise.iter() {
+            noise.resample();
+        }
+    }
+
+    // evaluate with the mean weights of every noisy layer,
+    // or go back to training with freshly drawn noise
+    pub fn eval(&mut self, evaluating: bool) {
+        self.evaluating = evaluating;
+        for noise in self.noise.iter() {
+            if evaluating {
+                noise.remove();
+            } else {
+                noise.resample();
+            }
+        }
+    }
+}
+
+impl Module for QNetwork {
+    fn forward(&self, xs: &Tensor) -> Tensor {
+        self.model.forward(xs)
+    }
+}
+
+pub fn q_network(vs: &nn::Path, config: &DqnConfig) -> QNetwork {
+    let mut noise = vec![];
+    let model: Box<dyn Module> = if config.dueling {
+        assert!(
+            config.distributional.is_none(),
+            "dueling heads output q values, not return distributions"
+        );
+        Box::new(DuelingNetwork::new(vs, config, &mut noise))
+    } else {
+        Box::new(mlp(vs, config, &mut noise))
+    };
+    QNetwork {
+        model,
+        noise,
+        evaluating: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agent::q_network::*;
+    use tch::Device;
+
+    #[test]
+    fn test_dueling_network() {
+        tch::manual_seed(0);
+        let vs = nn::VarStore::new(Device::Cpu);
+        let config = DqnConfig {
+            input: 3,
+            hidden1: 8,
+            hidden2: 8,
+            output: 4,
+            dueling: true,
+            ..DqnConfig::default()
+        };
+        let network = DuelingNetwork::new(&vs.root(), &config, &mut vec![]);
+        let states = Tensor::rand(&[5, 3], (Kind::Float, Device::Cpu));
+        let q = network.forward(&states);
+        assert_eq!(q.size(), vec![5, 4]);
+
+        // the advantages average to zero in every row,
+        // so the mean q value is the value stream
+        let value = network.value.forward(&network.body.forward(&states));
+        let mean = q.mean_dim(&[1], true, Kind::Float);
+        let difference = f64::from((mean - value).abs().max());
+        assert!(difference < 1e-5);
+    }
+
+    #[test]
+    fn test_eval() {
+        tch::manual_seed(0);
+        let vs = nn::VarStore::new(Device::Cpu);
+        let config = DqnConfig {
+            input: 3,
+            hidden1: 8,
+            hidden2: 8,
+            output: 4,
+            noisy: Some(0.5),
+            ..DqnConfig::default()
+        };
+        let mut network = q_network(&vs.root(), &config);
+        let states = Tensor::rand(&[5, 3], (Kind::Float, Device::Cpu));
+
+        // no noise is drawn while evaluating
+        network.eval(true);
+        let q = network.forward(&states);
+        network.reset_noise();
+        assert_eq!(network.forward(&states), q);
+
+        network.eval(false);
+        assert_ne!(network.forward(&states), q);
+    }
+}
@@ -0,0 +1,220 @@
+use rand::seq::index;
+use rand::Rng;
+use tch::Tensor;
+
+// transitions of a minibatch, stacked along the first dimension
+#[derive(Debug)]
+pub struct ReplayBatch {
+    // [batch_size, observation_size]
+    pub states: Tensor,
+    // [batch_size], Int64
+    pub actions: Tensor,
+    // [batch_size]
+    pub rewards: Tensor,
+    // [batch_size, observation_size]
+    pub next_states: Tensor,
+    // [batch_size], 1 where the episode ended
+    pub dones: Tensor,
+    // [batch_size], discount of the next state's value
+    pub discounts: Tensor,
+    // buffer slots the transitions came from
+    pub indices: Vec<usize>,
+}
+
+// fixed-capacity ring buffer of transitions; observations
+// are kept in contiguous f32 arrays, one row per slot, and
+// once full every push overwrites the oldest transition
+#[derive(Debug, Clone)]
+pub struct ReplayBuffer {
+    capacity: usize,
+    observation_size: usize,
+    states: Vec<f32>,
+    actions: Vec<i64>,
+    rewards: Vec<f32>,
+    next_states: Vec<f32>,
+    dones: Vec<f32>,
+    discounts: Vec<f32>,
+    len: usize,
+    // slot the next push writes to
+    next: usize,
+}
+
+impl ReplayBuffer {
+    pub fn new(capacity: usize, observation_size: usize) -> ReplayBuffer {
+        assert!(capacity > 0, "replay buffer needs room for a transition");
+        ReplayBuffer {
+            capacity,
+            observation_size,
+            states: vec![0.0; capacity * observation_size],
+            actions: vec![0; capacity],
+            rewards: vec![0.0; capacity],
+            next_states: vec![0.0; capacity * observation_size],
+            dones: vec![0.0; capacity],
+            discounts: vec![0.0; capacity],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // store a transition, returns the slot it went to;
+    // `discount` scales the value bootstrapped from `next_state`,
+    // gamma for one step, gamma^n for n-step transitions
+    pub fn push(
+        &mut self,
+        state: &[f32],
+        action: usize,
+        reward: f64,
+        next_state: &[f32],
+        done: bool,
+        discount: f64,
+    ) -> usize {
+        assert_eq!(state.len(), self.observation_size);
+        assert_eq!(next_state.len(), self.observation_size);
+        let slot = self.next;
+        let row = slot * self.observation_size..(slot + 1) * self.observation_size;
+        self.states[row.clone()].copy_from_slice(state);
+        self.next_states[row].copy_from_slice(next_state);
+        self.actions[slot] = action as i64;
+        self.rewards[slot] = reward as f32;
+        self.dones[slot] = done as i64 as f32;
+        self.discounts[slot] = discount as f32;
+
+        self.next = (self.next + 1) % self.capacity;
+        self.len = (self.len + 1).min(self.capacity);
+        slot
+    }
+
+    // the most recently pushed slot
+    pub fn last(&self) -> Option<usize> {
+        if self.is_empty() {
+            None
+        } else {
+            Some((self.next + self.capacity - 1) % self.capacity)
+        }
+    }
+
+    pub fn state(&self, slot: usize) -> &[f32] {
+        &self.states[slot * self.observation_size..(slot + 1) * self.observation_size]
+    }
+
+    pub fn action(&self, slot: usize) -> usize {
+        self.actions[slot] as usize
+    }
+
+    pub fn reward(&self, slot: usize) -> f64 {
+        self.rewards[slot] as f64
+    }
+
+    // uniformly chosen filled slots; without replacement
+    // every slot appears at most once
+    pub fn sample_indices<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        batch_size: usize,
+        replacement: bool,
+    ) -> Vec<usize> {
+        if replacement {
+            assert!(!self.is_empty(), "cannot sample an empty replay buffer");
+            (0..batch_size)
+                .map(|_| rng.gen_range(0..self.len))
+                .collect()
+        } else {
+            assert!(
+                batch_size <= self.len,
+                "cannot sample {} distinct transitions from {}",
+                batch_size,
+                self.len
+            );
+            index::sample(rng, self.len, batch_size).into_vec()
+        }
+    }
+
+    pub fn sample<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        batch_size: usize,
+        replacement: bool,
+    ) -> ReplayBatch {
+        let indices = self.sample_indices(rng, batch_size, replacement);
+        self.batch(indices)
+    }
+
+    // the transitions in these slots as batched tensors
+    pub fn batch(&self, indices: Vec<usize>) -> ReplayBatch {
+        let rows = |data: &[f32]| {
+            let mut out = Vec::with_capacity(indices.len() * self.observation_size);
+            for &i in indices.iter() {
+                out.extend_from_slice(
+                    &data[i * self.observation_size..(i + 1) * self.observation_size],
+                );
+            }
+            Tensor::of_slice(&out).view([indices.len() as i64, self.observation_size as i64])
+        };
+        let column =
+            |data: &[f32]| Tensor::of_slice(&indices.iter().map(|&i| data[i]).collect::<Vec<_>>());
+        ReplayBatch {
+            states: rows(&self.states),
+            actions: Tensor::of_slice(
+                &indices.iter().map(|&i| self.actions[i]).collect::<Vec<_>>(),
+            ),
+            rewards: column(&self.rewards),
+            next_states: rows(&self.next_states),
+            dones: column(&self.dones),
+            discounts: column(&self.discounts),
+            indices,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agent::replay_buffer::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut buffer = ReplayBuffer::new(3, 2);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.last(), None);
+        for i in 0..5 {
+            let x = i as f32;
+            buffer.push(&[x, x], i, i as f64, &[x + 1.0, x + 1.0], false, 0.9);
+        }
+        // 0 and 1 were overwritten by 3 and 4
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.last(), Some(1));
+        assert_eq!(buffer.state(0), &[3.0, 3.0]);
+        assert_eq!(buffer.action(1), 4);
+        assert_eq!(buffer.reward(2), 2.0);
+    }
+
+    #[test]
+    fn test_sample_indices() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut buffer = ReplayBuffer::new(10, 1);
+        for i in 0..6 {
+            buffer.push(&[0.0], i, 0.0, &[0.0], true, 0.9);
+        }
+
+        let with = buffer.sample_indices(&mut rng, 20, true);
+        assert_eq!(with.len(), 20);
+        assert!(with.iter().all(|&i| i < 6));
+
+        let mut without = buffer.sample_indices(&mut rng, 6, false);
+        without.sort_unstable();
+        assert_eq!(without, vec![0, 1, 2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,281 @@
+use rand::{Rng, RngCore};
+use rand_distr::{Distribution, Normal, StudentT};
+use std::fmt::Debug;
+
+// reward distribution behind one arm of a bandit; arms are
+// built through `new`, which checks their parameters
+pub trait Arm: Debug {
+    // expected reward of a pull
+    fn mean(&self) -> f64;
+
+    // reward of one pull
+    fn sample(&self, rng: &mut dyn RngCore) -> f64;
+}
+
+// reward 1 with probability `p`, 0 otherwise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bernoulli {
+    p: f64,
+}
+
+impl Bernoulli {
+    pub fn new(p: f64) -> Bernoulli {
+        assert!((0.0..=1.0).contains(&p), "probability {} out of [0, 1]", p);
+        Bernoulli { p }
+    }
+
+    pub fn p(&self) -> f64 {
+        self.p
+    }
+}
+
+impl Arm for Bernoulli {
+    fn mean(&self) -> f64 {
+        self.p
+    }
+
+    fn sample(&self, rng: &mut dyn RngCore) -> f64 {
+        if rng.gen_range(0.0..1.0) < self.p {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+// number of heads out of `trials` coins, each landing
+// heads with probability `p`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Binomial {
+    trials: i64,
+    p: f64,
+}
+
+impl Binomial {
+    pub fn new(trials: i64, p: f64) -> Binomial {
+        assert!(trials >= 0, "negative number of trials {}", trials);
+        assert!((0.0..=1.0).contains(&p), "probability {} out of [0, 1]", p);
+        Binomial { trials, p }
+    }
+
+    pub fn trials(&self) -> i64 {
+        self.trials
+    }
+
+    pub fn p(&self) -> f64 {
+        self.p
+    }
+}
+
+impl Arm for Binomial {
+    fn mean(&self) -> f64 {
+        self.trials as f64 * self.p
+    }
+
+    fn sample(&self, rng: &mut dyn RngCore) -> f64 {
+        let mut reward = 0.0;
+        for _ in 0..self.trials {
+            if rng.gen_range(0.0..1.0) < self.p {
+                reward += 1.0;
+            }
+        }
+        reward
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gaussian {
+    mean: f64,
+    std_dev: f64,
+}
+
+impl Gaussian {
+    pub fn new(mean: f64, std_dev: f64) -> Gaussian {
+        assert!(std_dev >= 0.0, "negative standard deviation {}", std_dev);
+        Gaussian { mean, std_dev }
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.std_dev
+    }
+}
+
+impl Arm for Gaussian {
+    fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    fn sample(&self, rng: &mut dyn RngCore) -> f64 {
+        Normal::new(self.mean, self.std_dev).unwrap().sample(rng)
+    }
+}
+
+// mean + scale * t, with t Student-t distributed; fewer
+// degrees of freedom give heavier tails, and above 1 the
+// mean is still finite
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeavyTailed {
+    mean: f64,
+    scale: f64,
+    degrees_of_freedom: f64,
+}
+
+impl HeavyTailed {
+    pub fn new(mean: f64, scale: f64, degrees_of_freedom: f64) -> HeavyTailed {
+        assert!(scale >= 0.0, "negative scale {}", scale);
+        assert!(
+            degrees_of_freedom > 1.0,
+            "student-t needs more than 1 degree of freedom for a mean"
+        );
+        HeavyTailed {
+            mean,
+            scale,
+            degrees_of_freedom,
+        }
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn degrees_of_freedom(&self) -> f64 {
+        self.degrees_of_freedom
+    }
+}
+
+impl Arm for HeavyTailed {
+    fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    fn sample(&self, rng: &mut dyn RngCore) -> f64 {
+        let t: f64 = StudentT::new(self.degrees_of_freedom).unwrap().sample(rng);
+        self.mean + self.scale * t
+    }
+}
+
+fn check_probability(p: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&p) {
+        Ok(())
+    } else {
+        Err(format!("probability {} out of [0, 1]", p))
+    }
+}
+
+// family of arms set by one parameter, for bandits whose
+// arms are built on the fly: the probability of Bernoulli
+// and binomial arms, the mean of Gaussian and heavy-tailed ones
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArmKind {
+    Bernoulli,
+    Binomial { trials: i64 },
+    Gaussian { std_dev: f64 },
+    HeavyTailed { scale: f64, degrees_of_freedom: f64 },
+}
+
+impl ArmKind {
+    pub fn arm(&self, parameter: f64) -> Box<dyn Arm> {
+        match *self {
+            ArmKind::Bernoulli => Box::new(Bernoulli::new(parameter)),
+            ArmKind::Binomial { trials } => Box::new(Binomial::new(trials, parameter)),
+            ArmKind::Gaussian { std_dev } => Box::new(Gaussian::new(parameter, std_dev)),
+            ArmKind::HeavyTailed {
+                scale,
+                degrees_of_freedom,
+            } => Box::new(HeavyTailed::new(parameter, scale, degrees_of_freedom)),
+        }
+    }
+
+    // the checks of the arm constructors, without building
+    // an arm: whether `parameter` gives a valid arm of this kind
+    pub fn validate(&self, parameter: f64) -> Result<(), String> {
+        match *self {
+            ArmKind::Bernoulli => check_probability(parameter),
+            ArmKind::Binomial { trials } => {
+                if trials < 0 {
+                    return Err(format!("negative number of trials {}", trials));
+                }
+                check_probability(parameter)
+            }
+            ArmKind::Gaussian { std_dev } => {
+                if std_dev < 0.0 {
+                    return Err(format!("negative standard deviation {}", std_dev));
+                }
+                Ok(())
+            }
+            ArmKind::HeavyTailed {
+                scale,
+                degrees_of_freedom,
+            } => {
+                if scale < 0.0 {
+                    return Err(format!("negative scale {}", scale));
+                }
+                if degrees_of_freedom <= 1.0 {
+                    return Err(String::from(
+                        "student-t needs more than 1 degree of freedom for a mean",
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // the parameter nearest to `parameter` that is valid
+    pub fn clamp(&self, parameter: f64) -> f64 {
+        match *self {
+            ArmKind::Bernoulli | ArmKind::Binomial { .. } => parameter.clamp(0.0, 1.0),
+            ArmKind::Gaussian { .. } | ArmKind::HeavyTailed { .. } => parameter,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bandit::arm::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn sample_mean(arm: &dyn Arm, pulls: usize) -> f64 {
+        let mut rng = StdRng::seed_from_u64(0);
+        (0..pulls).map(|_| arm.sample(&mut rng)).sum::<f64>() / pulls as f64
+    }
+
+    #[test]
+    fn test_arm_means() {
+        let arms: Vec<Box<dyn Arm>> = vec![
+            Box::new(Bernoulli::new(0.3)),
+            Box::new(Binomial::new(10, 0.7)),
+            Box::new(Gaussian::new(-1.0, 2.0)),
+            Box::new(HeavyTailed::new(2.0, 1.0, 3.0)),
+        ];
+        assert_eq!(arms[1].mean(), 7.0);
+        for arm in arms.iter() {
+            let estimate = sample_mean(arm.as_ref(), 20000);
+            assert!(
+                (estimate - arm.mean()).abs() < 0.1,
+                "{:?}: {}",
+                arm,
+                estimate
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(ArmKind::Bernoulli.validate(1.0), Ok(()));
+        assert!(ArmKind::Bernoulli.validate(1.5).is_err());
+        assert!(ArmKind::Binomial { trials: -1 }.validate(0.5).is_err());
+        assert!(ArmKind::Gaussian { std_dev: -1.0 }.validate(0.0).is_err());
+        let heavy = ArmKind::HeavyTailed {
+            scale: 1.0,
+            degrees_of_freedom: 3.0,
+        };
+        assert_eq!(heavy.validate(-5.0), Ok(()));
+        assert_eq!(heavy.arm(-5.0).mean(), -5.0);
+        let heavy = ArmKind::HeavyTailed {
+            scale: 1.0,
+            degrees_of_freedom: 1.0,
+        };
+        assert!(heavy.validate(0.0).is_err());
+    }
+}
@@ -0,0 +1,369 @@
+use crate::agent::exploration::{Boltzmann, ExplorationPolicy, Schedule};
+use crate::bandit::estimator::{Estimator, ValueEstimates};
+use crate::util::math::argmax;
+use rand::RngCore;
+use rand_distr::{Beta, Distribution, Normal};
+use std::fmt::Debug;
+
+// a strategy for picking arms that learns from the rewards
+// it gets back; agents know nothing of the arms but their count
+pub trait BanditAgent: Debug {
+    fn select(&mut self, rng: &mut dyn RngCore) -> usize;
+
+    // learn from the reward of pulling `arm`
+    fn update(&mut self, arm: usize, reward: f64);
+}
+
+// value estimates of every arm, with an exploration policy
+// (e.g. epsilon-greedy or softmax) choosing from them
+#[derive(Debug)]
+pub struct ActionValue {
+    pub policy: Box<dyn ExplorationPolicy>,
+    pub estimates: ValueEstimates,
+}
+
+impl ActionValue {
+    // sample-average estimates, for stationary bandits
+    pub fn new(arms: usize, policy: Box<dyn ExplorationPolicy>) -> ActionValue {
+        ActionValue::with_estimator(arms, Estimator::SampleAverage, policy)
+    }
+
+    pub fn with_estimator(
+        arms: usize,
+        estimator: Estimator,
+        policy: Box<dyn ExplorationPolicy>,
+    ) -> ActionValue {
+        ActionValue {
+            policy,
+            estimates: ValueEstimates::new(arms, estimator),
+        }
+    }
+}
+
+impl BanditAgent for ActionValue {
+    fn select(&mut self, rng: &mut dyn RngCore) -> usize {
+        self.policy.select(&self.estimates.values, rng)
+    }
+
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.estimates.update(arm, reward);
+    }
+}
+
+// UCB1 of Auer et al.: the arm maximising
+//     mean + c * sqrt(ln t / n)
+// after trying every arm once; c = sqrt(2) for rewards in [0, 1],
+// scale it with the reward range otherwise
+#[derive(Debug, Clone)]
+pub struct Ucb1 {
+    pub c: f64,
+    pub counts: Vec<usize>,
+    pub estimates: Vec<f64>,
+    plays: usize,
+}
+
+impl Ucb1 {
+    pub fn new(arms: usize, c: f64) -> Ucb1 {
+        Ucb1 {
+            c,
+            counts: vec![0; arms],
+            estimates: vec![0.0; arms],
+            plays: 0,
+        }
+    }
+}
+
+impl BanditAgent for Ucb1 {
+    fn select(&mut self, _rng: &mut dyn RngCore) -> usize {
+        if let Some(untried) = self.counts.iter().position(|&n| n == 0) {
+            return untried;
+        }
+        let log_plays = (self.plays as f64).ln();
+        let bounds: Vec<f64> = self
+            .estimates
+            .iter()
+            .zip(self.counts.iter())
+            .map(|(mean, &n)| mean + self.c * (log_plays / n as f64).sqrt())
+            .collect();
+        argmax(&bounds)
+    }
+
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.plays += 1;
+        self.counts[arm] += 1;
+        self.estimates[arm] += (reward - self.estimates[arm]) / self.counts[arm] as f64;
+    }
+}
+
+// Kullback-Leibler divergence between Bernoulli(p) and Bernoulli(q)
+pub fn bernoulli_kl(p: f64, q: f64) -> f64 {
+    let eps = 1e-15;
+    let p = p.clamp(eps, 1.0 - eps);
+    let q = q.clamp(eps, 1.0 - eps);
+    p * (p / q).ln() + (1.0 - p) * ((1.0 - p) / (1.0 - q)).ln()
+}
+
+// KL-UCB of Garivier & Cappe: the arm whose largest mean q with
+//     n * kl(mean, q) <= ln t + c ln ln t
+// is highest; rewards are divided by `scale` to fall in [0, 1]
+#[derive(Debug, Clone)]
+pub struct KlUcb {
+    pub c: f64,
+    pub scale: f64,
+    pub counts: Vec<usize>,
+    // sample averages of the scaled rewards
+    pub estimates: Vec<f64>,
+    plays: usize,
+}
+
+impl KlUcb {
+    pub fn new(arms: usize, scale: f64) -> KlUcb {
+        KlUcb {
+            c: 0.0,
+            scale,
+            counts: vec![0; arms],
+            estimates: vec![0.0; arms],
+            plays: 0,
+        }
+    }
+
+    // largest q in [mean, 1] with n * kl(mean, q) <= bound, by bisection
+    fn upper_bound(mean: f64, n: usize, bound: f64) -> f64 {
+        let (mut low, mut high) = (mean, 1.0);
+        for _ in 0..50 {
+            let q = (low + high) / 2.0;
+            if n as f64 * bernoulli_kl(mean, q) > bound {
+                high = q;
+            } else {
+                low = q;
+            }
+        }
+        low
+    }
+}
+
+impl BanditAgent for KlUcb {
+    fn select(&mut self, _rng: &mut dyn RngCore) -> usize {
+        if let Some(untried) = self.counts.iter().position(|&n| n == 0) {
+            return untried;
+        }
+        let log_plays = (self.plays as f64).ln();
+        let bound = log_plays + self.c * log_plays.ln().max(0.0);
+        let bounds: Vec<f64> = self
+            .estimates
+            .iter()
+            .zip(self.counts.iter())
+            .map(|(&mean, &n)| KlUcb::upper_bound(mean, n, bound))
+            .collect();
+        argmax(&bounds)
+    }
+
+    fn update(&mut self, arm: usize, reward: f64) {
+        let reward = (reward / self.scale).clamp(0.0, 1.0);
+        self.plays += 1;
+        self.counts[arm] += 1;
+        self.estimates[arm] += (reward - self.estimates[arm]) / self.counts[arm] as f64;
+    }
+}
+
+// Thompson sampling with a Beta(1, 1) prior on each arm's
+// success probability; rewards divided by `scale` count as
+// fractional successes, so binomial arms work as well
+#[derive(Debug, Clone)]
+pub struct BernoulliThompson {
+    pub scale: f64,
+    pub successes: Vec<f64>,
+    pub failures: Vec<f64>,
+}
+
+impl BernoulliThompson {
+    pub fn new(arms: usize, scale: f64) -> BernoulliThompson {
+        BernoulliThompson {
+            scale,
+            successes: vec![0.0; arms],
+            failures: vec![0.0; arms],
+        }
+    }
+}
+
+impl BanditAgent for BernoulliThompson {
+    fn select(&mut self, rng: &mut dyn RngCore) -> usize {
+        let samples: Vec<f64> = self
+            .successes
+            .iter()
+            .zip(self.failures.iter())
+            .map(|(s, f)| Beta::new(1.0 + s, 1.0 + f).unwrap().sample(rng))
+            .collect();
+        argmax(&samples)
+    }
+
+    fn update(&mut self, arm: usize, reward: f64) {
+        let reward = (reward / self.scale).clamp(0.0, 1.0);
+        self.successes[arm] += reward;
+        self.failures[arm] += 1.0 - reward;
+    }
+}
+
+// Thompson sampling for rewards with known standard deviation
+// `std_dev`, with a N(0, std_dev^2) prior on each mean; the
+// posterior after n rewards summing to s is
+//     N(s / (n + 1), std_dev^2 / (n + 1))
+#[derive(Debug, Clone)]
+pub struct GaussianThompson {
+    pub std_dev: f64,
+    pub counts: Vec<usize>,
+    pub sums: Vec<f64>,
+}
+
+impl GaussianThompson {
+    pub fn new(arms: usize, std_dev: f64) -> GaussianThompson {
+        GaussianThompson {
+            std_dev,
+            counts: vec![0; arms],
+            sums: vec![0.0; arms],
+        }
+    }
+}
+
+impl BanditAgent for GaussianThompson {
+    fn select(&mut self, rng: &mut dyn RngCore) -> usize {
+        let samples: Vec<f64> = self
+            .sums
+            .iter()
+            .zip(self.counts.iter())
+            .map(|(sum, &n)| {
+                let pseudo_count = n as f64 + 1.0;
+                Normal::new(sum / pseudo_count, self.std_dev / pseudo_count.sqrt())
+                    .unwrap()
+                    .sample(rng)
+            })
+            .collect();
+        argmax(&samples)
+    }
+
+    fn update(&mut self, arm: usize, reward: f64) {
+        self.counts[arm] += 1;
+        self.sums[arm] += reward;
+    }
+}
+
+// gradient bandit of Sutton & Barto (2.8): arms are drawn from
+// a softmax over preferences H, and after reward R for arm a
+//     H(b) += alpha * (R - baseline) * (1[a = b] - pi(b))
+// with the average reward so far as baseline
+#[derive(Debug, Clone)]
+pub struct GradientBandit {
+    pub alpha: f64,
+    // compare rewards with the average reward, or with 0
+    pub use_baseline: bool,
+    pub preferences: Vec<f64>,
+    pub baseline: f64,
+    plays: usize,
+}
+
+impl GradientBandit {
+    pub fn new(arms: usize, alpha: f64) -> GradientBandit {
+        GradientBandit {
+            alpha,
+            use_baseline: true,
+            preferences: vec![0.0; arms],
+            baseline: 0.0,
+            plays: 0,
+        }
+    }
+
+    // probability of drawing each arm
+    pub fn probabilities(&self) -> Vec<f64> {
+        let softmax = Boltzmann::new(Schedule::Constant(1.0));
+        softmax.probabilities(&self.preferences)
+    }
+}
+
+impl BanditAgent for GradientBandit {
+    fn select(&mut self, rng: &mut dyn RngCore) -> usize {
+        let mut softmax = Boltzmann::new(Schedule::Constant(1.0));
+        softmax.select(&self.preferences, rng)
+    }
+
+    fn update(&mut self, arm: usize, reward: f64) {
+        let probabilities = self.probabilities();
+        let advantage = if self.use_baseline {
+            reward - self.baseline
+        } else {
+            reward
+        };
+        for (b, preference) in self.preferences.iter_mut().enumerate() {
+            let indicator = if b == arm { 1.0 } else { 0.0 };
+            *preference += self.alpha * advantage * (indicator - probabilities[b]);
+        }
+        self.plays += 1;
+        self.baseline += (reward - self.baseline) / self.plays as f64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agent::exploration::EpsilonGreedy;
+    use crate::bandit::bandit_agent::*;
+    use crate::bandit::multi_arm_bandit::{Bandit, MultiArmBandit};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // share of the last half of `plays` pulls that went to the best arm
+    fn late_optimal_rate(agent: &mut dyn BanditAgent, bandit: &mut MultiArmBandit) -> f64 {
+        let mut rng = StdRng::seed_from_u64(0);
+        let plays = 2000;
+        let mut optimal = 0;
+        for t in 0..plays {
+            let arm = agent.select(&mut rng);
+            agent.update(arm, bandit.pull(arm));
+            if t >= plays / 2 && arm == bandit.optimal_arm() {
+                optimal += 1;
+            }
+        }
+        optimal as f64 / (plays / 2) as f64
+    }
+
+    #[test]
+    fn test_agents_find_best_arm() {
+        let probabilities = [0.1, 0.5, 0.8, 0.3];
+        let agents: Vec<Box<dyn BanditAgent>> = vec![
+            Box::new(ActionValue::new(
+                4,
+                Box::new(EpsilonGreedy::new(Schedule::Constant(0.1))),
+            )),
+            Box::new(Ucb1::new(4, 2f64.sqrt())),
+            Box::new(KlUcb::new(4, 1.0)),
+            Box::new(BernoulliThompson::new(4, 1.0)),
+            Box::new(GaussianThompson::new(4, 0.5)),
+            Box::new(GradientBandit::new(4, 0.1)),
+        ];
+        for mut agent in agents {
+            let mut bandit = MultiArmBandit::bernoulli(&probabilities, 1);
+            let rate = late_optimal_rate(agent.as_mut(), &mut bandit);
+            assert!(rate > 0.8, "{:?}: {}", agent, rate);
+        }
+    }
+
+    #[test]
+    fn test_kl_upper_bound() {
+        assert!(bernoulli_kl(0.5, 0.5).abs() < 1e-12);
+        assert!(bernoulli_kl(0.2, 0.6) > 0.0);
+        let q = KlUcb::upper_bound(0.5, 10, 1.0);
+        assert!(q > 0.5 && q < 1.0);
+        assert!((10.0 * bernoulli_kl(0.5, q) - 1.0).abs() < 1e-6);
+        // more pulls, tighter bound
+        assert!(KlUcb::upper_bound(0.5, 100, 1.0) < q);
+    }
+
+    #[test]
+    fn test_gradient_update() {
+        let mut agent = GradientBandit::new(2, 0.5);
+        agent.use_baseline = false;
+        agent.update(0, 1.0);
+        // pi was uniform: H(0) += 0.5 * 0.5, H(1) -= 0.5 * 0.5
+        assert_eq!(agent.preferences, vec![0.25, -0.25]);
+        assert_eq!(agent.baseline, 1.0);
+        assert!(agent.probabilities()[0] > 0.5);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::bandit::arm::ArmKind;
+use crate::bandit::non_stationary::Drift;
+use crate::env::environment::{Environment, Observation, StepResult};
+use crate::util::math::argmax;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+// contextual bandit: every pull happens in a random state,
+// seen as a one-hot observation, and each state has its own
+// arms; the state is redrawn uniformly after every pull
+#[derive(Debug, Clone)]
+pub struct ContextBandit {
+    pub states: usize,
+    pub actions: usize,
+    pub kind: ArmKind,
+    // parameter of the arm of every state-action pair,
+    // row-major: matrix[state * actions + action]
+    pub matrix: Vec<f64>,
+    // how the matrix changes as the bandit is played, the
+    // switches setting the whole matrix
+    pub drift: Drift,
+    // pulls so far
+    pub plays: usize,
+    state: usize,
+    rng: StdRng,
+}
+
+impl ContextBandit {
+    // arm parameters drawn uniformly from [0, 1)
+    pub fn new(states: usize, actions: usize, kind: ArmKind, seed: u64) -> ContextBandit {
+        assert!(
+            states > 0 && actions > 0,
+            "a contextual bandit needs states and actions"
+        );
+        let mut rng = StdRng::seed_from_u64(seed);
+        let state = rng.gen_range(0..states);
+        let matrix: Vec<f64> = (0..states * actions)
+            .map(|_| rng.gen_range(0.0..1.0))
+            .collect();
+        for &parameter in matrix.iter() {
+            kind.validate(parameter)
+                .unwrap_or_else(|message| panic!("{}", message));
+        }
+        ContextBandit {
+            states,
+            actions,
+            kind,
+            matrix,
+            drift: Drift::Stationary,
+            plays: 0,
+            state,
+            rng,
+        }
+    }
+
+    pub fn with_matrix(
+        states: usize,
+        actions: usize,
+        kind: ArmKind,
+        matrix: Vec<f64>,
+        seed: u64,
+    ) -> ContextBandit {
+        assert!(
+            states > 0 && actions > 0,
+            "a contextual bandit needs states and actions"
+        );
+        assert_eq!(
+            matrix.len(),
+            states * actions,
+            "matrix needs one parameter per state-action pair"
+        );
+        for &parameter in matrix.iter() {
+            kind.validate(parameter)
+                .unwrap_or_else(|message| panic!("{}", message));
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        ContextBandit {
+            states,
+            actions,
+            kind,
+            matrix,
+            drift: Drift::Stationary,
+            plays: 0,
+            state: rng.gen_range(0..states),
+            rng,
+        }
+    }
+
+    // the same bandit with its matrix drifting, e.g.
+    // `ContextBandit::new(10, 10, kind, seed).with_drift(Drift::RandomWalk { std_dev: 0.01 })`
+    pub fn with_drift(mut self, drift: Drift) -> ContextBandit {
+        drift.validate(self.kind, self.matrix.len());
+        self.drift = drift;
+        self
+    }
+
+    pub fn state(&self) -> usize {
+        self.state
+    }
+
+    pub fn parameter(&self, state: usize, action: usize) -> f64 {
+        self.matrix[state * self.actions + action]
+    }
+
+    // expected reward of every action in `state`
+    pub fn means(&self, state: usize) -> Vec<f64> {
+        (0..self.actions)
+            .map(|action| self.kind.arm(self.parameter(state, action)).mean())
+            .collect()
+    }
+
+    // the best action in `state`, the first one on ties
+    pub fn optimal_action(&self, state: usize) -> usize {
+        argmax(&self.means(state))
+    }
+
+    // reward of `action` in the current state, then the
+    // matrix drifts and a new state is drawn
+    pub fn pull(&mut self, action: usize) -> f64 {
+        let arm = self.kind.arm(self.parameter(self.state, action));
+        let reward = arm.sample(&mut self.rng);
+        self.plays += 1;
+        self.drift
+            .apply(self.kind, &mut self.matrix, self.plays, &mut self.rng);
+        self.state = self.rng.gen_range(0..self.states);
+        reward
+    }
+
+    // one-hot encoding of the current state
+    fn observation(&self) -> Observation {
+        (0..self.states)
+            .map(|x| if self.state == x { 1.0 } else { 0.0 })
+            .collect()
+    }
+}
+
+impl Environment for ContextBandit {
+    fn action_count(&self) -> usize {
+        self.actions
+    }
+
+    fn observation_size(&self) -> usize {
+        self.states
+    }
+
+    fn reset(&mut self, seed: Option<u64>) -> Observation {
+        if let Some(seed) = seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+        self.state = self.rng.gen_range(0..self.states);
+        self.observation()
+    }
+
+    fn step(&mut self, action: usize) -> StepResult {
+        let reward = self.pull(action);
+        StepResult {
+            observation: self.observation(),
+            reward,
+            terminated: false,
+            truncated: false,
+            info: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bandit::context_bandit::*;
+
+    #[test]
+    fn test_independent_sizes() {
+        let mut bandit = ContextBandit::new(3, 12, ArmKind::Bernoulli, 0);
+        assert_eq!(bandit.matrix.len(), 36);
+        assert_eq!(bandit.action_count(), 12);
+        let observation = bandit.reset(None);
+        assert_eq!(observation.len(), 3);
+        assert_eq!(observation.iter().sum::<f64>(), 1.0);
+        assert_eq!(observation[bandit.state()], 1.0);
+
+        // with more than 10 actions, state 1 action 0 and state 0
+        // action 10 are different slots of the matrix
+        let matrix: Vec<f64> = (0..24).map(|i| i as f64 / 23.0).collect();
+        let bandit = ContextBandit::with_matrix(2, 12, ArmKind::Bernoulli, matrix, 0);
+        assert_eq!(bandit.parameter(1, 0), 12.0 / 23.0);
+        assert_eq!(bandit.parameter(0, 10), 10.0 / 23.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of [0, 1]")]
+    fn test_invalid_matrix() {
+        ContextBandit::with_matrix(1, 2, ArmKind::Bernoulli, vec![0.5, 1.5], 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "a contextual bandit needs states and actions")]
+    fn test_no_states() {
+        ContextBandit::new(0, 3, ArmKind::Bernoulli, 0);
+    }
+
+    #[test]
+    fn test_matrix_rewards() {
+        let matrix = vec![0.0, 1.0, 0.0, 1.0, 0.0, 0.0];
+        let mut bandit =
+            ContextBandit::with_matrix(2, 3, ArmKind::Binomial { trials: 5 }, matrix, 0);
+        assert_eq!(bandit.means(1), vec![5.0, 0.0, 0.0]);
+        assert_eq!(bandit.optimal_action(0), 1);
+        for _ in 0..20 {
+            let best = bandit.optimal_action(bandit.state());
+            assert_eq!(bandit.step(best).reward, 5.0);
+        }
+
+        let a = ContextBandit::new(10, 10, ArmKind::Bernoulli, 7);
+        let b = ContextBandit::new(10, 10, ArmKind::Bernoulli, 7);
+        assert_eq!(a.matrix, b.matrix);
+        assert_eq!(a.state(), b.state());
+    }
+
+    #[test]
+    fn test_drift() {
+        let drift = Drift::Switches(vec![(1, vec![0.0, 1.0, 1.0, 0.0])]);
+        let mut bandit =
+            ContextBandit::with_matrix(2, 2, ArmKind::Bernoulli, vec![1.0, 0.0, 0.0, 1.0], 0)
+                .with_drift(drift);
+        assert_eq!(bandit.optimal_action(0), 0);
+        bandit.pull(0);
+        assert_eq!(bandit.optimal_action(0), 1);
+        assert_eq!(bandit.optimal_action(1), 0);
+
+        let mut bandit = ContextBandit::new(3, 4, ArmKind::Bernoulli, 0)
+            .with_drift(Drift::RandomWalk { std_dev: 0.5 });
+        let matrix = bandit.matrix.clone();
+        for _ in 0..10 {
+            bandit.pull(0);
+        }
+        assert_ne!(bandit.matrix, matrix);
+        assert!(bandit.matrix.iter().all(|p| (0.0..=1.0).contains(p)));
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+
+// how an arm's value is estimated from its rewards
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Estimator {
+    // mean of every reward so far, for stationary arms
+    SampleAverage,
+    // Q += alpha * (R - Q), weighting recent rewards
+    // exponentially more
+    ConstantStep { alpha: f64 },
+    // mean of the arm's last `window` rewards
+    SlidingWindow { window: usize },
+    // rewards weighted by gamma^(plays since), counting plays
+    // of every arm, so arms left alone fade as well
+    Discounted { gamma: f64 },
+}
+
+// value estimates of every arm of a bandit
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueEstimates {
+    pub estimator: Estimator,
+    pub values: Vec<f64>,
+    pub counts: Vec<usize>,
+    // latest rewards of every arm, for `SlidingWindow`
+    windows: Vec<VecDeque<f64>>,
+    // discounted reward sums and pull counts, for `Discounted`
+    sums: Vec<f64>,
+    weights: Vec<f64>,
+}
+
+impl ValueEstimates {
+    pub fn new(arms: usize, estimator: Estimator) -> ValueEstimates {
+        ValueEstimates {
+            estimator,
+            values: vec![0.0; arms],
+            counts: vec![0; arms],
+            windows: vec![VecDeque::new(); arms],
+            sums: vec![0.0; arms],
+            weights: vec![0.0; arms],
+        }
+    }
+
+    pub fn update(&mut self, arm: usize, reward: f64) {
+        self.counts[arm] += 1;
+        match self.estimator {
+            Estimator::SampleAverage => {
+                self.values[arm] += (reward - self.values[arm]) / self.counts[arm] as f64;
+            }
+            Estimator::ConstantStep { alpha } => {
+                self.values[arm] += alpha * (reward - self.values[arm]);
+            }
+            Estimator::SlidingWindow { window } => {
+                let rewards = &mut self.windows[arm];
+                rewards.push_back(reward);
+                if rewards.len() > window.max(1) {
+                    rewards.pop_front();
+                }
+                self.values[arm] = rewards.iter().sum::<f64>() / rewards.len() as f64;
+            }
+            Estimator::Discounted { gamma } => {
+                for (sum, weight) in self.sums.iter_mut().zip(self.weights.iter_mut()) {
+                    *sum *= gamma;
+                    *weight *= gamma;
+                }
+                self.sums[arm] += reward;
+                self.weights[arm] += 1.0;
+                self.values[arm] = self.sums[arm] / self.weights[arm];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bandit::estimator::*;
+
+    fn estimate(estimator: Estimator, rewards: &[f64]) -> f64 {
+        let mut estimates = ValueEstimates::new(2, estimator);
+        for &reward in rewards.iter() {
+            estimates.update(0, reward);
+        }
+        estimates.values[0]
+    }
+
+    #[test]
+    fn test_estimators() {
+        let rewards = [0.0, 0.0, 4.0, 8.0];
+        assert_eq!(estimate(Estimator::SampleAverage, &rewards), 3.0);
+        // 0.5 * 4 = 2, then 2 + 0.5 * (8 - 2) = 5
+        assert_eq!(
+            estimate(Estimator::ConstantStep { alpha: 0.5 }, &rewards),
+            5.0
+        );
+        assert_eq!(
+            estimate(Estimator::SlidingWindow { window: 2 }, &rewards),
+            6.0
+        );
+        // (4 * 0.5 + 8) / (0.125 + 0.25 + 0.5 + 1)
+        let discounted = estimate(Estimator::Discounted { gamma: 0.5 }, &rewards);
+        assert!((discounted - 10.0 / 1.875).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_discount_counts_every_play() {
+        let mut estimates = ValueEstimates::new(2, Estimator::Discounted { gamma: 0.5 });
+        estimates.update(0, 1.0);
+        estimates.update(1, 3.0);
+        estimates.update(0, 0.0);
+        // arm 0: (1 * 0.25 + 0) / (0.25 + 1)
+        assert!((estimates.values[0] - 0.2).abs() < 1e-12);
+        assert_eq!(estimates.values[1], 3.0);
+        assert_eq!(estimates.counts, vec![2, 1]);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::bandit::bandit_agent::BanditAgent;
+use crate::bandit::multi_arm_bandit::Bandit;
+use crate::plot::xy_plot::Band;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// z value of a two-sided 95% normal confidence interval
+const Z_95: f64 = 1.96;
+
+// a statistic averaged over independent runs, with the
+// half-width of its 95% confidence interval at every point
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Series {
+    pub mean: Vec<f64>,
+    pub half_width: Vec<f64>,
+}
+
+impl Series {
+    // `values[run][i]`, every run of the same length
+    pub fn from_runs(values: &[Vec<f64>]) -> Series {
+        let runs = values.len() as f64;
+        let len = values.first().map_or(0, |run| run.len());
+        let mut series = Series::default();
+        for i in 0..len {
+            let mean = values.iter().map(|run| run[i]).sum::<f64>() / runs;
+            let variance = if values.len() > 1 {
+                values
+                    .iter()
+                    .map(|run| (run[i] - mean).powi(2))
+                    .sum::<f64>()
+                    / (runs - 1.0)
+            } else {
+                0.0
+            };
+            series.mean.push(mean);
+            series.half_width.push(Z_95 * (variance / runs).sqrt());
+        }
+        series
+    }
+
+    // (x, mean) points, for `xy_line_plot`
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        self.mean
+            .iter()
+            .enumerate()
+            .map(|(i, &mean)| (i as f64, mean))
+            .collect()
+    }
+
+    // (x, mean, half-width) points, for `xy_band_plot`
+    pub fn band(&self) -> Band {
+        self.mean
+            .iter()
+            .zip(self.half_width.iter())
+            .enumerate()
+            .map(|(i, (&mean, &half_width))| (i as f64, mean, half_width))
+            .collect()
+    }
+}
+
+// what one agent did over many runs, indexed by play
+// (or by arm for `pull_counts`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExperimentResult {
+    pub rewards: Series,
+    // sum over the plays so far of the best mean minus
+    // the mean of the arm pulled
+    pub cumulative_regret: Series,
+    // share of runs that pulled a best arm
+    pub optimal_rate: Series,
+    // pulls of every arm by the end of a run
+    pub pull_counts: Series,
+}
+
+// play `runs` independent games of `plays` pulls each, with a
+// fresh bandit from `make_bandit(seed)` and a fresh agent from
+// `make_agent(arms)` every run; `seed` fixes every run
+pub fn run_experiment<B, FB, FA>(
+    mut make_bandit: FB,
+    mut make_agent: FA,
+    runs: usize,
+    plays: usize,
+    seed: u64,
+) -> ExperimentResult
+where
+    B: Bandit,
+    FB: FnMut(u64) -> B,
+    FA: FnMut(usize) -> Box<dyn BanditAgent>,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut rewards = vec![];
+    let mut regrets = vec![];
+    let mut optimal = vec![];
+    let mut pull_counts = vec![];
+    for _ in 0..runs {
+        let mut bandit = make_bandit(rng.gen());
+        let mut agent = make_agent(bandit.arms());
+        let mut agent_rng = StdRng::seed_from_u64(rng.gen());
+
+        let mut run_rewards = Vec::with_capacity(plays);
+        let mut run_regrets = Vec::with_capacity(plays);
+        let mut run_optimal = Vec::with_capacity(plays);
+        let mut counts = vec![0.0; bandit.arms()];
+        let mut regret = 0.0;
+        for _ in 0..plays {
+            // the means are read every play, they may drift
+            let means = bandit.means();
+            let best = means.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let arm = agent.select(&mut agent_rng);
+            let reward = bandit.pull(arm);
+            agent.update(arm, reward);
+
+            regret += best - means[arm];
+            run_rewards.push(reward);
+            run_regrets.push(regret);
+            run_optimal.push(if means[arm] == best { 1.0 } else { 0.0 });
+            counts[arm] += 1.0;
+        }
+        rewards.push(run_rewards);
+        regrets.push(run_regrets);
+        optimal.push(run_optimal);
+        pull_counts.push(counts);
+    }
+
+    ExperimentResult {
+        rewards: Series::from_runs(&rewards),
+        cumulative_regret: Series::from_runs(&regrets),
+        optimal_rate: Series::from_runs(&optimal),
+        pull_counts: Series::from_runs(&pull_counts),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agent::exploration::Greedy;
+    use crate::bandit::bandit_agent::{ActionValue, Ucb1};
+    use crate::bandit::experiment::*;
+    use crate::bandit::multi_arm_bandit::MultiArmBandit;
+
+    #[test]
+    fn test_series() {
+        let series = Series::from_runs(&[vec![1.0, 2.0], vec![3.0, 2.0]]);
+        assert_eq!(series.mean, vec![2.0, 2.0]);
+        // sample std 2^0.5 over 2 runs: 1.96 * 1
+        assert!((series.half_width[0] - 1.96).abs() < 1e-12);
+        assert_eq!(series.half_width[1], 0.0);
+        assert_eq!(series.points(), vec![(0.0, 2.0), (1.0, 2.0)]);
+    }
+
+    #[test]
+    fn test_run_experiment() {
+        // greedy on a bandit whose first arm never pays sticks to it
+        let result = run_experiment(
+            |seed| MultiArmBandit::bernoulli(&[0.0, 1.0], seed),
+            |arms| Box::new(ActionValue::new(arms, Box::new(Greedy))),
+            3,
+            10,
+            0,
+        );
+        assert_eq!(result.cumulative_regret.mean[9], 10.0);
+        assert!(result.optimal_rate.mean.iter().all(|&rate| rate == 0.0));
+        assert_eq!(result.pull_counts.mean, vec![10.0, 0.0]);
+
+        let result = run_experiment(
+            |seed| MultiArmBandit::bernoulli(&[0.2, 0.8], seed),
+            |arms| Box::new(Ucb1::new(arms, 2f64.sqrt())),
+            20,
+            500,
+            0,
+        );
+        assert_eq!(result.pull_counts.mean.iter().sum::<f64>(), 500.0);
+        assert!(result.pull_counts.mean[1] > result.pull_counts.mean[0]);
+        assert!(result.optimal_rate.mean[499] > 0.8);
+    }
+}
@@ -0,0 +1,8 @@
+pub mod arm;
+pub mod bandit_agent;
+pub mod context_bandit;
+pub mod estimator;
+pub mod experiment;
+pub mod multi_arm_bandit;
+pub mod n_arm_bandit;
+pub mod non_stationary;
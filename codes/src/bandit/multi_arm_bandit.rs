@@ -0,0 +1,161 @@
+use crate::bandit::arm::{Arm, Bernoulli, Binomial, Gaussian, HeavyTailed};
+use crate::env::environment::{Environment, Observation, StepResult};
+use crate::util::math::argmax;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+// a row of arms to pull; the true means are exposed so
+// experiments can measure how far an agent is from the best
+pub trait Bandit {
+    fn arms(&self) -> usize;
+
+    // reward of pulling `arm` once
+    fn pull(&mut self, arm: usize) -> f64;
+
+    // expected reward of every arm
+    fn means(&self) -> Vec<f64>;
+
+    // the arm with the highest mean, the first one on ties
+    fn optimal_arm(&self) -> usize {
+        argmax(&self.means())
+    }
+
+    fn optimal_mean(&self) -> f64 {
+        self.means()[self.optimal_arm()]
+    }
+}
+
+// bandit over arbitrary reward distributions
+#[derive(Debug)]
+pub struct MultiArmBandit {
+    pub arms: Vec<Box<dyn Arm>>,
+    rng: StdRng,
+}
+
+impl MultiArmBandit {
+    pub fn new(arms: Vec<Box<dyn Arm>>, seed: u64) -> MultiArmBandit {
+        MultiArmBandit {
+            arms,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn bernoulli(probabilities: &[f64], seed: u64) -> MultiArmBandit {
+        let arms = probabilities
+            .iter()
+            .map(|&p| Box::new(Bernoulli::new(p)) as Box<dyn Arm>)
+            .collect();
+        MultiArmBandit::new(arms, seed)
+    }
+
+    pub fn binomial(probabilities: &[f64], trials: i64, seed: u64) -> MultiArmBandit {
+        let arms = probabilities
+            .iter()
+            .map(|&p| Box::new(Binomial::new(trials, p)) as Box<dyn Arm>)
+            .collect();
+        MultiArmBandit::new(arms, seed)
+    }
+
+    // every arm shares the same standard deviation
+    pub fn gaussian(means: &[f64], std_dev: f64, seed: u64) -> MultiArmBandit {
+        let arms = means
+            .iter()
+            .map(|&mean| Box::new(Gaussian::new(mean, std_dev)) as Box<dyn Arm>)
+            .collect();
+        MultiArmBandit::new(arms, seed)
+    }
+
+    // student-t noise with `degrees_of_freedom` around each mean
+    pub fn heavy_tailed(
+        means: &[f64],
+        scale: f64,
+        degrees_of_freedom: f64,
+        seed: u64,
+    ) -> MultiArmBandit {
+        let arms = means
+            .iter()
+            .map(|&mean| {
+                Box::new(HeavyTailed::new(mean, scale, degrees_of_freedom)) as Box<dyn Arm>
+            })
+            .collect();
+        MultiArmBandit::new(arms, seed)
+    }
+
+    // the testbed of Sutton & Barto: means drawn from N(0, 1),
+    // rewards from N(mean, 1)
+    pub fn testbed(arms: usize, seed: u64) -> MultiArmBandit {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let means: Vec<f64> = (0..arms)
+            .map(|_| rng.sample::<f64, _>(rand_distr::StandardNormal))
+            .collect();
+        MultiArmBandit::gaussian(&means, 1.0, rng.gen())
+    }
+}
+
+impl Bandit for MultiArmBandit {
+    fn arms(&self) -> usize {
+        self.arms.len()
+    }
+
+    fn pull(&mut self, arm: usize) -> f64 {
+        self.arms[arm].sample(&mut self.rng)
+    }
+
+    fn means(&self) -> Vec<f64> {
+        self.arms.iter().map(|arm| arm.mean()).collect()
+    }
+}
+
+// like `NArmBandit`, observations are empty and episodes
+// never end; resetting with a seed re-seeds the rewards
+impl Environment for MultiArmBandit {
+    fn action_count(&self) -> usize {
+        self.arms.len()
+    }
+
+    fn observation_size(&self) -> usize {
+        0
+    }
+
+    fn reset(&mut self, seed: Option<u64>) -> Observation {
+        if let Some(seed) = seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+        vec![]
+    }
+
+    fn step(&mut self, action: usize) -> StepResult {
+        StepResult {
+            observation: vec![],
+            reward: self.pull(action),
+            terminated: false,
+            truncated: false,
+            info: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bandit::multi_arm_bandit::*;
+
+    #[test]
+    fn test_optimal_arm() {
+        let mut bandit = MultiArmBandit::bernoulli(&[0.2, 0.9, 0.9, 0.0], 0);
+        assert_eq!(bandit.optimal_arm(), 1);
+        assert_eq!(bandit.optimal_mean(), 0.9);
+        assert_eq!(bandit.pull(3), 0.0);
+
+        let bandit = MultiArmBandit::binomial(&[0.5, 0.1], 10, 0);
+        assert_eq!(bandit.means(), vec![5.0, 1.0]);
+
+        let a = MultiArmBandit::testbed(10, 3);
+        let b = MultiArmBandit::testbed(10, 3);
+        assert_eq!(a.means(), b.means());
+        assert_eq!(
+            a.optimal_mean(),
+            a.means().iter().copied().fold(f64::MIN, f64::max)
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use crate::bandit::arm::{Arm, Binomial};
+use crate::bandit::multi_arm_bandit::Bandit;
+use crate::env::environment::{Environment, Observation, StepResult};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+// n-armed bandit where pulling an arm flips `trials` coins,
+// each landing heads with the arm's hidden probability,
+// and the reward is the number of heads
+#[derive(Debug)]
+pub struct NArmBandit {
+    // hidden probabilities associated with each arm
+    pub probabilities: Vec<f64>,
+    pub trials: i64,
+    rng: StdRng,
+}
+
+impl NArmBandit {
+    // bandit with uniformly random arm probabilities
+    pub fn new(arms: usize, trials: i64) -> NArmBandit {
+        NArmBandit::with_rng(arms, trials, StdRng::from_entropy())
+    }
+
+    // bandit whose probabilities and rewards are fully determined by `seed`
+    pub fn with_seed(arms: usize, trials: i64, seed: u64) -> NArmBandit {
+        NArmBandit::with_rng(arms, trials, StdRng::seed_from_u64(seed))
+    }
+
+    pub fn with_rng(arms: usize, trials: i64, mut rng: StdRng) -> NArmBandit {
+        let probabilities = (0..arms).map(|_| rng.gen_range(0.0..1.0)).collect();
+        NArmBandit {
+            probabilities,
+            trials,
+            rng,
+        }
+    }
+
+    pub fn with_probabilities(probabilities: Vec<f64>, trials: i64, seed: u64) -> NArmBandit {
+        NArmBandit {
+            probabilities,
+            trials,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn arm(&self, arm: usize) -> Binomial {
+        Binomial::new(self.trials, self.probabilities[arm])
+    }
+}
+
+impl Bandit for NArmBandit {
+    fn arms(&self) -> usize {
+        self.probabilities.len()
+    }
+
+    // simulate get reward
+    fn pull(&mut self, arm: usize) -> f64 {
+        self.arm(arm).sample(&mut self.rng)
+    }
+
+    fn means(&self) -> Vec<f64> {
+        (0..self.arms()).map(|arm| self.arm(arm).mean()).collect()
+    }
+}
+
+// a bandit has no state, so observations are empty
+// and episodes never end; resetting with a seed only
+// re-seeds the rewards, the arm probabilities are kept
+impl Environment for NArmBandit {
+    fn action_count(&self) -> usize {
+        self.arms()
+    }
+
+    fn observation_size(&self) -> usize {
+        0
+    }
+
+    fn reset(&mut self, seed: Option<u64>) -> Observation {
+        if let Some(seed) = seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+        vec![]
+    }
+
+    fn step(&mut self, action: usize) -> StepResult {
+        StepResult {
+            observation: vec![],
+            reward: self.pull(action),
+            terminated: false,
+            truncated: false,
+            info: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bandit::n_arm_bandit::*;
+
+    #[test]
+    fn test_pull_bounds() {
+        let mut bandit = NArmBandit::with_probabilities(vec![0.0, 1.0, 0.5], 10, 0);
+        assert_eq!(bandit.action_count(), 3);
+        assert_eq!(bandit.step(0).reward, 0.0);
+        assert_eq!(bandit.step(1).reward, 10.0);
+        let reward = bandit.step(2).reward;
+        assert!((0.0..=10.0).contains(&reward));
+        assert_eq!(bandit.means(), vec![0.0, 10.0, 5.0]);
+        assert_eq!(bandit.optimal_arm(), 1);
+    }
+
+    #[test]
+    fn test_seeded_rewards() {
+        let mut a = NArmBandit::with_seed(5, 10, 11);
+        let mut b = NArmBandit::with_seed(5, 10, 11);
+        assert_eq!(a.probabilities, b.probabilities);
+
+        let rewards_a: Vec<f64> = (0..20).map(|i| a.step(i % 5).reward).collect();
+        let rewards_b: Vec<f64> = (0..20).map(|i| b.step(i % 5).reward).collect();
+        assert_eq!(rewards_a, rewards_b);
+    }
+}
@@ -0,0 +1,223 @@
+use crate::bandit::arm::ArmKind;
+use crate::bandit::multi_arm_bandit::Bandit;
+use crate::env::environment::{Environment, Observation, StepResult};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal};
+use std::collections::HashMap;
+
+// how the arm parameters change as the bandit is played
+#[derive(Debug, Clone, PartialEq)]
+pub enum Drift {
+    Stationary,
+    // after every pull each parameter moves by N(0, std_dev^2),
+    // clamped to what the arm kind allows
+    RandomWalk { std_dev: f64 },
+    // (play, parameters): after that many pulls, at least one,
+    // the parameters are replaced; plays strictly increase
+    Switches(Vec<(usize, Vec<f64>)>),
+}
+
+impl Drift {
+    // panics unless the drift fits `parameters` parameters
+    // of `kind`, checking every switch
+    pub fn validate(&self, kind: ArmKind, parameters: usize) {
+        match self {
+            Drift::Stationary => {}
+            Drift::RandomWalk { std_dev } => {
+                assert!(*std_dev >= 0.0, "negative random walk std_dev {}", std_dev);
+            }
+            Drift::Switches(switches) => {
+                for (play, switched) in switches.iter() {
+                    assert!(
+                        *play > 0,
+                        "a switch at play 0 would never apply, start from its parameters instead"
+                    );
+                    assert_eq!(
+                        switched.len(),
+                        parameters,
+                        "every switch must set all {} parameters",
+                        parameters
+                    );
+                    for &parameter in switched.iter() {
+                        kind.validate(parameter)
+                            .unwrap_or_else(|message| panic!("{}", message));
+                    }
+                }
+                assert!(
+                    switches.windows(2).all(|pair| pair[0].0 < pair[1].0),
+                    "switch plays must strictly increase"
+                );
+            }
+        }
+    }
+
+    // move `parameters` of `kind` once `plays` pulls are done
+    pub fn apply(&self, kind: ArmKind, parameters: &mut Vec<f64>, plays: usize, rng: &mut StdRng) {
+        match self {
+            Drift::Stationary => {}
+            Drift::RandomWalk { std_dev } => {
+                let step = Normal::new(0.0, *std_dev).unwrap();
+                for parameter in parameters.iter_mut() {
+                    *parameter = kind.clamp(*parameter + step.sample(rng));
+                }
+            }
+            Drift::Switches(switches) => {
+                if let Some((_, switched)) = switches.iter().find(|(play, _)| *play == plays) {
+                    *parameters = switched.clone();
+                }
+            }
+        }
+    }
+}
+
+// bandit whose arm means move over time, so the best arm
+// at the start need not stay the best
+#[derive(Debug)]
+pub struct NonStationaryBandit {
+    pub kind: ArmKind,
+    // current probability or mean of every arm
+    pub parameters: Vec<f64>,
+    pub drift: Drift,
+    // pulls so far
+    pub plays: usize,
+    rng: StdRng,
+}
+
+impl NonStationaryBandit {
+    pub fn new(
+        kind: ArmKind,
+        parameters: Vec<f64>,
+        drift: Drift,
+        seed: u64,
+    ) -> NonStationaryBandit {
+        for &parameter in parameters.iter() {
+            kind.validate(parameter)
+                .unwrap_or_else(|message| panic!("{}", message));
+        }
+        drift.validate(kind, parameters.len());
+        NonStationaryBandit {
+            kind,
+            parameters,
+            drift,
+            plays: 0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    // the random-walk testbed of Sutton & Barto (exercise 2.5):
+    // all means start equal at 0, then walk with std 0.01
+    pub fn random_walk(arms: usize, seed: u64) -> NonStationaryBandit {
+        NonStationaryBandit::new(
+            ArmKind::Gaussian { std_dev: 1.0 },
+            vec![0.0; arms],
+            Drift::RandomWalk { std_dev: 0.01 },
+            seed,
+        )
+    }
+}
+
+impl Bandit for NonStationaryBandit {
+    fn arms(&self) -> usize {
+        self.parameters.len()
+    }
+
+    fn pull(&mut self, arm: usize) -> f64 {
+        let reward = self.kind.arm(self.parameters[arm]).sample(&mut self.rng);
+        self.plays += 1;
+        self.drift
+            .apply(self.kind, &mut self.parameters, self.plays, &mut self.rng);
+        reward
+    }
+
+    fn means(&self) -> Vec<f64> {
+        self.parameters
+            .iter()
+            .map(|&parameter| self.kind.arm(parameter).mean())
+            .collect()
+    }
+}
+
+// observations are empty and episodes never end; the
+// arms keep whatever drift they have had on reset
+impl Environment for NonStationaryBandit {
+    fn action_count(&self) -> usize {
+        self.parameters.len()
+    }
+
+    fn observation_size(&self) -> usize {
+        0
+    }
+
+    fn reset(&mut self, seed: Option<u64>) -> Observation {
+        if let Some(seed) = seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+        vec![]
+    }
+
+    fn step(&mut self, action: usize) -> StepResult {
+        StepResult {
+            observation: vec![],
+            reward: self.pull(action),
+            terminated: false,
+            truncated: false,
+            info: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bandit::non_stationary::*;
+
+    #[test]
+    fn test_switches() {
+        let drift = Drift::Switches(vec![(2, vec![1.0, 0.0]), (3, vec![0.5, 0.5])]);
+        let mut bandit = NonStationaryBandit::new(ArmKind::Bernoulli, vec![0.0, 1.0], drift, 0);
+        assert_eq!(bandit.optimal_arm(), 1);
+        assert_eq!(bandit.pull(1), 1.0);
+        assert_eq!(bandit.pull(0), 0.0);
+        assert_eq!(bandit.means(), vec![1.0, 0.0]);
+        assert_eq!(bandit.optimal_arm(), 0);
+        bandit.pull(0);
+        assert_eq!(bandit.means(), vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_random_walk() {
+        let drift = Drift::RandomWalk { std_dev: 0.5 };
+        let mut bandit = NonStationaryBandit::new(ArmKind::Bernoulli, vec![0.5; 3], drift, 0);
+        for _ in 0..100 {
+            bandit.pull(0);
+        }
+        assert_ne!(bandit.means(), vec![0.5; 3]);
+        assert!(bandit.parameters.iter().all(|p| (0.0..=1.0).contains(p)));
+
+        let mut bandit = NonStationaryBandit::random_walk(10, 0);
+        assert_eq!(bandit.means(), vec![0.0; 10]);
+        bandit.pull(0);
+        assert!(bandit.means().iter().all(|m| m.abs() < 0.1));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of [0, 1]")]
+    fn test_invalid_switch() {
+        let drift = Drift::Switches(vec![(5, vec![0.5, 2.0])]);
+        NonStationaryBandit::new(ArmKind::Bernoulli, vec![0.5, 0.5], drift, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "a switch at play 0 would never apply")]
+    fn test_switch_at_start() {
+        let drift = Drift::Switches(vec![(0, vec![1.0, 0.0])]);
+        NonStationaryBandit::new(ArmKind::Bernoulli, vec![0.5, 0.5], drift, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "switch plays must strictly increase")]
+    fn test_switch_order() {
+        let drift = Drift::Switches(vec![(3, vec![1.0, 0.0]), (2, vec![0.0, 1.0])]);
+        NonStationaryBandit::new(ArmKind::Bernoulli, vec![0.5, 0.5], drift, 0);
+    }
+}
@@ -0,0 +1,90 @@
+use drl::agent::benchmark::moving_average;
+use drl::agent::exploration::{Boltzmann, EpsilonGreedy, Schedule};
+use drl::bandit::bandit_agent::{
+    ActionValue, BanditAgent, BernoulliThompson, GaussianThompson, GradientBandit, KlUcb, Ucb1,
+};
+use drl::bandit::experiment::run_experiment;
+use drl::bandit::multi_arm_bandit::MultiArmBandit;
+use drl::plot::xy_plot::{xy_band_plot, xy_line_plot};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// a fresh agent for a bandit with `arms` Bernoulli arms
+fn make_agent(name: &str, arms: usize) -> Box<dyn BanditAgent> {
+    match name {
+        "epsilon-greedy" => Box::new(ActionValue::new(
+            arms,
+            Box::new(EpsilonGreedy::new(Schedule::Constant(0.1))),
+        )),
+        "softmax" => Box::new(ActionValue::new(
+            arms,
+            Box::new(Boltzmann::new(Schedule::Constant(0.1))),
+        )),
+        "UCB1" => Box::new(Ucb1::new(arms, 2f64.sqrt())),
+        "KL-UCB" => Box::new(KlUcb::new(arms, 1.0)),
+        "Bernoulli Thompson" => Box::new(BernoulliThompson::new(arms, 1.0)),
+        "Gaussian Thompson" => Box::new(GaussianThompson::new(arms, 0.5)),
+        "gradient" => Box::new(GradientBandit::new(arms, 0.1)),
+        _ => panic!("unknown agent {}", name),
+    }
+}
+
+fn main() {
+    // a single seed makes the whole run reproducible
+    let seed = 0;
+
+    let arms = 10;
+    let runs = 200;
+    let plays = 1000;
+    let names = [
+        "epsilon-greedy",
+        "softmax",
+        "UCB1",
+        "KL-UCB",
+        "Bernoulli Thompson",
+        "Gaussian Thompson",
+        "gradient",
+    ];
+
+    let mut regrets = vec![];
+    let mut optimal_rates = vec![];
+    for name in names.iter() {
+        // the same seed gives every agent the same bandits
+        let result = run_experiment(
+            |seed| {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let probabilities: Vec<f64> = (0..arms).map(|_| rng.gen_range(0.0..1.0)).collect();
+                MultiArmBandit::bernoulli(&probabilities, rng.gen())
+            },
+            |arms| make_agent(name, arms),
+            runs,
+            plays,
+            seed,
+        );
+        println!(
+            "{}: regret {:.1} +/- {:.1}, optimal arm {:.1}%",
+            name,
+            result.cumulative_regret.mean[plays - 1],
+            result.cumulative_regret.half_width[plays - 1],
+            100.0 * result.optimal_rate.mean[plays - 1],
+        );
+        regrets.push((name.to_string(), result.cumulative_regret.band()));
+        optimal_rates.push((
+            name.to_string(),
+            moving_average(&result.optimal_rate.mean, 20),
+        ));
+    }
+
+    xy_band_plot(
+        String::from("bandit_regret.svg"),
+        regrets,
+        String::from("Plays"),
+        String::from("Cumulative Regret"),
+    );
+    xy_line_plot(
+        String::from("bandit_optimal_action.svg"),
+        optimal_rates,
+        String::from("Plays"),
+        String::from("Optimal Action Rate"),
+    );
+}
@@ -0,0 +1,89 @@
+use drl::agent::distributional::Support;
+use drl::agent::dqn::{DqnAgent, DqnConfig};
+use drl::env::environment::Environment;
+use drl::grid::grid_board::PieceKind;
+use drl::grid::grid_world::{Action, GameStatus, GridWorld};
+use drl::plot::xy_plot::{loss_plot, xy_line_plot};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// free squares next to a pit, where the return distribution
+// of a careless move has a second mode at the pit reward
+fn pit_neighbours(game: &GridWorld) -> Vec<(i64, i64)> {
+    let occupied: Vec<(i64, i64)> = [PieceKind::Goal, PieceKind::Pit, PieceKind::Wall]
+        .iter()
+        .flat_map(|&kind| game.positions(kind))
+        .collect();
+    let mut squares = vec![];
+    for pit in game.positions(PieceKind::Pit) {
+        for action in Action::ALL.iter() {
+            let (di, dj) = action.direction();
+            let pos = (pit.0 + di, pit.1 + dj);
+            let inside =
+                (0..game.board.size).contains(&pos.0) && (0..game.board.size).contains(&pos.1);
+            if inside && !occupied.contains(&pos) && !squares.contains(&pos) {
+                squares.push(pos);
+            }
+        }
+    }
+    squares
+}
+
+fn main() {
+    // a single seed makes the whole run reproducible
+    let seed = 0;
+    tch::manual_seed(seed as i64);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let support = Support::default();
+    let config = DqnConfig {
+        replay: true,
+        target_network: true,
+        distributional: Some(support),
+        ..DqnConfig::default()
+    };
+    let mut agent = DqnAgent::with_seed(config, rng.gen());
+
+    let mut game = GridWorld::with_seed(4, String::from("static"), rng.gen());
+    game.max_moves = Some(50);
+    let log = agent.train(&mut game, 1000);
+    loss_plot(String::from("c51.svg"), &log.losses);
+
+    let mut test_game = GridWorld::with_seed(4, String::from("static"), rng.gen());
+    test_game.max_moves = Some(15);
+    if agent.play(&mut test_game, true) == GameStatus::Won {
+        println!("Game won!");
+    }
+
+    // one plot per square, one line per action
+    game.reset(None);
+    let values = support.values();
+    for pos in pit_neighbours(&game) {
+        game.board.components.get_mut("Player").unwrap().pos = pos;
+        let state = agent.observe(&game.board.render_array());
+        let distributions = agent.return_distribution(&DqnAgent::state_tensor(&state));
+
+        let mut series = vec![];
+        for (a, probabilities) in distributions.iter().enumerate() {
+            let action = format!("{:?}", Action::from_index(a));
+            println!(
+                "{:?} {}: expected return {}",
+                pos,
+                action,
+                support.mean(probabilities)
+            );
+            let xy = values
+                .iter()
+                .copied()
+                .zip(probabilities.iter().copied())
+                .collect();
+            series.push((action, xy));
+        }
+        xy_line_plot(
+            format!("c51_{}_{}.svg", pos.0, pos.1),
+            series,
+            String::from("return"),
+            String::from("probability"),
+        );
+    }
+}
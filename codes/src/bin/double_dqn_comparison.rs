@@ -0,0 +1,30 @@
+use drl::agent::benchmark::{benchmark_dqn, moving_average};
+use drl::agent::dqn::DqnConfig;
+use drl::plot::xy_plot::xy_line_plot;
+
+fn main() {
+    let seeds = [0, 1, 2];
+    let dqn = DqnConfig {
+        replay: true,
+        target_network: true,
+        ..DqnConfig::default()
+    };
+    let double_dqn = DqnConfig {
+        double_dqn: true,
+        ..dqn.clone()
+    };
+
+    let mut series = vec![];
+    for (name, config) in [("DQN", dqn), ("Double DQN", double_dqn)].iter() {
+        let result = benchmark_dqn(config, "player", &seeds, 1000, 100);
+        println!("{}: win percentage {}", name, result.win_rate);
+        series.push((name.to_string(), moving_average(&result.returns, 50)));
+    }
+
+    xy_line_plot(
+        String::from("double_dqn_comparison.svg"),
+        series,
+        String::from("epoch"),
+        String::from("return"),
+    );
+}
@@ -0,0 +1,31 @@
+use drl::agent::benchmark::{benchmark_dqn, moving_average};
+use drl::agent::dqn::DqnConfig;
+use drl::plot::xy_plot::xy_line_plot;
+
+// the random mode is where the plain mlp struggles most
+fn main() {
+    let seeds = [0, 1, 2];
+    let dqn = DqnConfig {
+        replay: true,
+        target_network: true,
+        ..DqnConfig::default()
+    };
+    let dueling = DqnConfig {
+        dueling: true,
+        ..dqn.clone()
+    };
+
+    let mut series = vec![];
+    for (name, config) in [("DQN", dqn), ("Dueling DQN", dueling)].iter() {
+        let result = benchmark_dqn(config, "random", &seeds, 1000, 100);
+        println!("{}: win percentage {}", name, result.win_rate);
+        series.push((name.to_string(), moving_average(&result.returns, 50)));
+    }
+
+    xy_line_plot(
+        String::from("dueling_comparison.svg"),
+        series,
+        String::from("epoch"),
+        String::from("return"),
+    );
+}
@@ -0,0 +1,39 @@
+use drl::grid::grid_world::{Action, GridWorld};
+use drl::planning::dynamic_programming::{policy_iteration, value_iteration, PlanningConfig};
+
+// optimal values and moves of every square, to compare
+// against what the qlearning* binaries learn
+fn main() {
+    let world = GridWorld::new(4, String::from("static"));
+    let grid_mdp = world.to_mdp();
+    // the gamma the qlearning* binaries train with
+    let config = PlanningConfig {
+        gamma: 0.9,
+        ..PlanningConfig::default()
+    };
+
+    let by_values = value_iteration(&grid_mdp.mdp, &config);
+    let by_policy = policy_iteration(&grid_mdp.mdp, &config);
+    println!(
+        "value iteration: {} sweeps, policy iteration: {} improvements",
+        by_values.iterations, by_policy.iterations
+    );
+
+    world.display();
+    let size = world.board.size;
+    for i in 0..size {
+        let row: Vec<String> = (0..size)
+            .map(|j| match grid_mdp.state_index((i, j)) {
+                Some(s) if grid_mdp.mdp.terminal[s] => format!("{:>14}", "end"),
+                Some(s) => format!(
+                    "{:>7.3} {:<6}",
+                    by_values.values[s],
+                    format!("{:?}", Action::from_index(by_values.policy[s]))
+                ),
+                None => format!("{:>14}", "-"),
+            })
+            .collect();
+        println!("{}", row.join(" "));
+    }
+    println!("Optimal return from the start: {}", by_values.values[0]);
+}
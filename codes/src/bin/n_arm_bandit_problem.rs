@@ -1,80 +1,37 @@
+use drl::agent::exploration::{EpsilonGreedy, Schedule};
+use drl::bandit::bandit_agent::{ActionValue, BanditAgent};
+use drl::bandit::n_arm_bandit::NArmBandit;
+use drl::env::environment::Environment;
 use drl::plot::xy_plot::xy_scatter_plot;
-use rand::Rng;
-
-// simulate get reward
-fn get_reward(proability: f64, n: i64) -> f64 {
-    let mut rng = rand::thread_rng();
-
-    let mut reward = 0.0;
-    for _ in 0..n {
-        if rng.gen_range(0.0..1.0) < proability {
-            reward += 1.0;
-        }
-    }
-    reward
-}
-
-// calcuate avg reward
-fn update_record(record: &mut Vec<Reward>, action: usize, reward: f64) {
-    let new_reward = (record[action].frequence as f64 * record[action].reward + reward)
-        / (record[action].frequence + 1) as f64;
-    record[action].frequence += 1;
-    record[action].reward = new_reward;
-}
-
-// get best action index
-fn get_best_arm(record: &Vec<Reward>) -> usize {
-    let mut max_index = 0;
-    let mut max_value = 0.0;
-    for (index, value) in record.iter().enumerate() {
-        if max_value < value.reward {
-            max_value = value.reward;
-            max_index = index;
-        }
-    }
-    max_index
-}
-
-#[derive(Debug, Default, Clone)]
-struct Reward {
-    frequence: i64,
-    reward: f64,
-}
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 fn main() {
-    let mut rng = rand::thread_rng();
+    // a single seed makes the whole run reproducible
+    let seed = 0;
+    let mut rng = StdRng::seed_from_u64(seed);
 
     // 摇杆个数
     let narms = 10;
 
     // epsilon-greedy系数
-    let epsilon = 0.2;
+    let policy = EpsilonGreedy::new(Schedule::Constant(0.2));
 
     // hidden probabilities associated with each arm
-    let probabilities: Vec<f64> = (0..narms).map(|_| rng.gen_range(0.0..1.0)).collect();
-    println!("{:?}", probabilities);
+    let mut env = NArmBandit::with_seed(narms, 10, rng.gen());
+    env.reset(None);
+    println!("{:?}", env.probabilities);
 
-    // state-action-reward
-    let mut record: Vec<Reward> = vec![
-        Reward {
-            frequence: 0,
-            reward: 0.0,
-        };
-        10
-    ];
+    // sample-average estimates of each arm's reward
+    let mut agent = ActionValue::new(narms, Box::new(policy));
 
     // accumulate rewards
     let mut rewards: Vec<(f64, f64)> = vec![];
     for i in 0..500 {
-        let choice: usize;
-        if rng.gen_range(0.0..1.0) > epsilon {
-            choice = get_best_arm(&record);
-        } else {
-            choice = rng.gen_range(0..narms);
-        }
+        let choice = agent.select(&mut rng);
 
-        let reward = get_reward(probabilities[choice], 10);
-        update_record(&mut record, choice, reward);
+        let reward = env.step(choice).reward;
+        agent.update(choice, reward);
         if i == 0 {
             rewards.push((i as f64, reward));
         } else {
@@ -0,0 +1,71 @@
+use drl::agent::benchmark::moving_average;
+use drl::agent::exploration::{EpsilonGreedy, Schedule};
+use drl::bandit::bandit_agent::ActionValue;
+use drl::bandit::estimator::Estimator;
+use drl::bandit::experiment::run_experiment;
+use drl::bandit::non_stationary::NonStationaryBandit;
+use drl::plot::xy_plot::xy_line_plot;
+
+fn main() {
+    // a single seed makes the whole run reproducible
+    let seed = 0;
+
+    // exercise 2.5 of Sutton & Barto: epsilon-greedy on ten arms
+    // whose means random-walk away from 0
+    let arms = 10;
+    let runs = 100;
+    let plays = 10000;
+    let estimators = [
+        ("sample average", Estimator::SampleAverage),
+        ("constant step 0.1", Estimator::ConstantStep { alpha: 0.1 }),
+        (
+            "sliding window 200",
+            Estimator::SlidingWindow { window: 200 },
+        ),
+        ("discounted 0.99", Estimator::Discounted { gamma: 0.99 }),
+    ];
+
+    let mut rewards = vec![];
+    let mut optimal_rates = vec![];
+    for (name, estimator) in estimators.iter() {
+        let result = run_experiment(
+            |seed| NonStationaryBandit::random_walk(arms, seed),
+            |arms| {
+                let policy = EpsilonGreedy::new(Schedule::Constant(0.1));
+                Box::new(ActionValue::with_estimator(
+                    arms,
+                    *estimator,
+                    Box::new(policy),
+                ))
+            },
+            runs,
+            plays,
+            seed,
+        );
+        let last = &result.rewards.mean[plays / 2..];
+        println!(
+            "{}: average reward over the second half {:.3}, optimal arm {:.1}%",
+            name,
+            last.iter().sum::<f64>() / last.len() as f64,
+            100.0 * result.optimal_rate.mean[plays - 1],
+        );
+        rewards.push((name.to_string(), moving_average(&result.rewards.mean, 100)));
+        optimal_rates.push((
+            name.to_string(),
+            moving_average(&result.optimal_rate.mean, 100),
+        ));
+    }
+
+    xy_line_plot(
+        String::from("non_stationary_reward.svg"),
+        rewards,
+        String::from("Plays"),
+        String::from("Avg Reward"),
+    );
+    xy_line_plot(
+        String::from("non_stationary_optimal_action.svg"),
+        optimal_rates,
+        String::from("Plays"),
+        String::from("Optimal Action Rate"),
+    );
+}
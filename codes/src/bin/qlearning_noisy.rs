@@ -0,0 +1,44 @@
+use drl::agent::dqn::{DqnAgent, DqnConfig};
+use drl::grid::grid_world::{GameStatus, GridWorld};
+use drl::plot::xy_plot::loss_plot;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn main() {
+    // a single seed makes the whole run reproducible
+    let seed = 0;
+    tch::manual_seed(seed as i64);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // explore through learned weight noise instead of
+    // epsilon-greedy; evaluation games are played without noise
+    let config = DqnConfig {
+        replay: true,
+        memory_size: 1000,
+        batch_size: 200,
+        target_network: true,
+        sync_frequency: 500,
+        noisy: Some(0.5),
+        ..DqnConfig::default()
+    };
+    let mut agent = DqnAgent::with_seed(config, rng.gen());
+
+    let mut game = GridWorld::with_seed(4, String::from("static"), rng.gen());
+    game.max_moves = Some(50);
+    let log = agent.train(&mut game, 1000);
+    loss_plot(String::from("qlearning_noisy.svg"), &log.losses);
+
+    let max_games = 1000;
+    let mut wins = 0;
+    for _i in 0..max_games {
+        let mut game = GridWorld::with_seed(4, String::from("static"), rng.gen());
+        game.max_moves = Some(15);
+        if agent.play(&mut game, false) == GameStatus::Won {
+            wins += 1;
+        }
+    }
+
+    let win_rate = wins as f64 / max_games as f64;
+    println!("Games played: {}, # of wins: {}", max_games, wins);
+    println!("Win percentage: {}", win_rate);
+}
@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+// flat observation vector handed to agents
+pub type Observation = Vec<f64>;
+
+// everything an agent learns from one environment step
+#[derive(Debug, Clone)]
+pub struct StepResult {
+    pub observation: Observation,
+    pub reward: f64,
+    // the episode reached a terminal state (e.g. won or lost)
+    pub terminated: bool,
+    // the episode was cut short from outside the MDP (e.g. a move limit)
+    pub truncated: bool,
+    // extra diagnostics, not meant to be used for learning
+    pub info: HashMap<String, f64>,
+}
+
+impl StepResult {
+    // whether the episode is over, for either reason
+    pub fn done(&self) -> bool {
+        self.terminated || self.truncated
+    }
+}
+
+// gym-style environment with a discrete action space
+pub trait Environment {
+    // number of discrete actions, indexed 0..action_count()
+    fn action_count(&self) -> usize;
+
+    // length of the observation vector
+    fn observation_size(&self) -> usize;
+
+    // start a new episode and return its first observation
+    // seed: optional seed for the environment's randomness
+    fn reset(&mut self, seed: Option<u64>) -> Observation;
+
+    // apply an action (by index) and advance the environment one step
+    fn step(&mut self, action: usize) -> StepResult;
+}
@@ -1,46 +1,156 @@
 use rand::Rng;
 use std::collections::HashMap;
 
+// the kinds of pieces a world is built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PieceKind {
+    Player,
+    Goal,
+    Pit,
+    Wall,
+}
+
+impl PieceKind {
+    // all kinds, in observation plane order
+    pub const ALL: [PieceKind; 4] = [
+        PieceKind::Player,
+        PieceKind::Goal,
+        PieceKind::Pit,
+        PieceKind::Wall,
+    ];
+
+    // code drawn on the board
+    pub fn code(&self) -> &'static str {
+        match self {
+            PieceKind::Player => "P",
+            PieceKind::Goal => "+",
+            PieceKind::Pit => "-",
+            PieceKind::Wall => "W",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<PieceKind> {
+        PieceKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.code() == code)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PieceKind::Player => "Player",
+            PieceKind::Goal => "Goal",
+            PieceKind::Pit => "Pit",
+            PieceKind::Wall => "Wall",
+        }
+    }
+
+    // name of the n-th piece of this kind, counting from 1:
+    // "Wall", "Wall2", "Wall3", ...
+    pub fn piece_name(&self, n: usize) -> String {
+        if n <= 1 {
+            String::from(self.name())
+        } else {
+            format!("{}{}", self.name(), n)
+        }
+    }
+}
+
 // base unit of board
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BoardPiece {
     pub name: String,
     pub code: String,
     pub pos: (i64, i64),
+    // index of the plane this piece is drawn on in `render_array`
+    pub layer: usize,
 }
 
-pub fn rand_pos(low: i64, high: i64) -> (i64, i64) {
-    let mut rng = rand::thread_rng();
+pub fn rand_pos<R: Rng + ?Sized>(rng: &mut R, low: i64, high: i64) -> (i64, i64) {
     (rng.gen_range(low..high), rng.gen_range(low..high))
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GridBoard {
     pub size: i64,
     pub components: HashMap<String, BoardPiece>,
+    // piece code drawn on each plane of `render_array`
+    layers: Vec<String>,
 }
 
 impl GridBoard {
     pub fn new(size: i64) -> GridBoard {
         GridBoard {
             size,
-            components: HashMap::new()
+            components: HashMap::new(),
+            layers: vec![],
         }
     }
 
+    // board whose planes are fixed up front, so the layout does not
+    // depend on which pieces happen to be placed
+    pub fn with_layout(size: i64, layout: &[&str]) -> GridBoard {
+        GridBoard {
+            size,
+            components: HashMap::new(),
+            layers: layout.iter().map(|code| String::from(*code)).collect(),
+        }
+    }
+
+    // Planes are assigned in insertion order: the first piece with a
+    // new code gets the next free layer and pieces sharing a code
+    // share its layer.
     pub fn add_piece(&mut self, name: String, code: String, pos: (i64, i64)) {
-        let piece = BoardPiece{
-            name: name.clone(), 
-            code, pos
+        let layer = match self.layers.iter().position(|c| *c == code) {
+            Some(layer) => layer,
+            None => {
+                self.layers.push(code.clone());
+                self.layers.len() - 1
+            }
         };
-        self.components.insert(name.clone(), piece);
+        let piece = BoardPiece {
+            name: name.clone(),
+            code,
+            pos,
+            layer,
+        };
+        self.components.insert(name, piece);
     }
 
+    // number of planes in `render_array`
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    // piece codes in plane order, i.e. `layout()[i]` is the code
+    // of the pieces drawn on plane `i` of `render_array`
+    pub fn layout(&self) -> Vec<String> {
+        self.layers.clone()
+    }
+
+    // positions of all pieces with the given code
+    pub fn positions(&self, code: &str) -> Vec<(i64, i64)> {
+        self.components
+            .values()
+            .filter(|piece| piece.code == code)
+            .map(|piece| piece.pos)
+            .collect()
+    }
+
+    // pieces sorted by layer, then by name
+    pub fn ordered_pieces(&self) -> Vec<&BoardPiece> {
+        let mut pieces: Vec<&BoardPiece> = self.components.values().collect();
+        pieces.sort_by(|a, b| (a.layer, &a.name).cmp(&(b.layer, &b.name)));
+        pieces
+    }
+
+    // when pieces overlap, the one on the lowest layer is shown
     pub fn render(&self) {
+        let pieces = self.ordered_pieces();
         for i in 0..self.size {
             for j in 0..self.size {
                 let mut special_pos = false;
-                for (_name, piece) in self.components.iter() {
+                for piece in pieces.iter() {
                     if (i, j) == piece.pos {
                         print!(" {} ", piece.code);
                         special_pos = true;
@@ -51,19 +161,20 @@ impl GridBoard {
                     print!(" * ");
                 }
             }
-            println!("");
+            println!();
         }
-        println!("");
+        println!();
     }
 
+    // one size x size plane per layer, stacked in layer order
+    // (see `layout`), with 1.0 where a piece of that layer stands
     pub fn render_array(&self) -> Vec<f64> {
         let len = self.size as usize;
-        let mut pattern: Vec<f64> =  vec![0.0; len * len * self.components.len()];
-        let mut frame_index: usize = 0;
         let frame_size = len * len;
-        for (_name, piece) in self.components.iter() {
-            pattern[frame_index * frame_size + piece.pos.0 as usize * len + piece.pos.1 as usize] = 1.0;
-            frame_index += 1;
+        let mut pattern: Vec<f64> = vec![0.0; frame_size * self.layer_count()];
+        for piece in self.components.values() {
+            pattern[piece.layer * frame_size + piece.pos.0 as usize * len + piece.pos.1 as usize] =
+                1.0;
         }
         pattern
     }
@@ -80,4 +191,30 @@ mod tests {
         board.add_piece(String::from("Goal"), String::from("O"), (1, 1));
         board.render();
     }
+
+    #[test]
+    fn test_board_layout() {
+        let mut board = GridBoard::new(4);
+        board.add_piece(String::from("Wall"), String::from("W"), (0, 0));
+        board.add_piece(String::from("Player"), String::from("P"), (1, 1));
+        board.add_piece(String::from("Goal"), String::from("+"), (2, 2));
+        // moving a piece keeps its plane
+        board.add_piece(String::from("Wall"), String::from("W"), (3, 3));
+        assert_eq!(board.layout(), vec!["W", "P", "+"]);
+
+        let pattern = board.render_array();
+        assert_eq!(pattern.len(), 3 * 16);
+        assert_eq!(pattern[15], 1.0);
+        assert_eq!(pattern[16 + 5], 1.0);
+        assert_eq!(pattern[32 + 10], 1.0);
+        assert_eq!(pattern.iter().sum::<f64>(), 3.0);
+
+        // a fixed layout keeps empty planes
+        let mut board = GridBoard::with_layout(4, &["P", "+", "-"]);
+        board.add_piece(String::from("Pit"), String::from("-"), (0, 0));
+        board.add_piece(String::from("Pit2"), String::from("-"), (0, 1));
+        assert_eq!(board.layout(), vec!["P", "+", "-"]);
+        assert_eq!(board.render_array()[32..34], [1.0, 1.0]);
+        assert_eq!(board.positions("-").len(), 2);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,239 @@
+use crate::grid::grid_board::{GridBoard, PieceKind};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+// Plain text board layout, one line per row and one character per
+// square, using the codes `GridBoard::render` prints:
+//
+//     P * * *
+//     * W - *
+//     * * * +
+//     * - * *
+//
+// `*` or `.` is an empty square, whitespace between squares is
+// optional, blank lines and lines starting with `#` are skipped.
+// A map is square and has exactly one player, at least one goal
+// and any number of walls and pits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridMap {
+    pub size: i64,
+    // (code, position) of every piece
+    pub pieces: Vec<(String, (i64, i64))>,
+}
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(std::io::Error),
+    // the map has no rows
+    Empty,
+    // row has a different number of squares than there are rows
+    NotSquare { row: usize, len: usize, size: usize },
+    UnknownCode { row: usize, col: usize, code: char },
+    // there must be exactly one player and at least one goal,
+    // but `count` pieces with this code were found
+    PieceCount { code: &'static str, count: usize },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapError::Io(e) => write!(f, "io error: {}", e),
+            MapError::Empty => write!(f, "map has no rows"),
+            MapError::NotSquare { row, len, size } => write!(
+                f,
+                "row {} has {} squares, expected {} for a square map",
+                row, len, size
+            ),
+            MapError::UnknownCode { row, col, code } => {
+                write!(f, "unknown code '{}' at ({}, {})", code, row, col)
+            }
+            MapError::PieceCount { code, count } => write!(
+                f,
+                "map has {} '{}' pieces, it needs one player and at least one goal",
+                count, code
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+impl From<std::io::Error> for MapError {
+    fn from(e: std::io::Error) -> MapError {
+        MapError::Io(e)
+    }
+}
+
+impl GridMap {
+    pub fn parse(text: &str) -> Result<GridMap, MapError> {
+        let rows: Vec<Vec<char>> = text
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect())
+            .collect();
+        if rows.is_empty() {
+            return Err(MapError::Empty);
+        }
+
+        let size = rows.len();
+        let mut pieces = vec![];
+        for (i, row) in rows.iter().enumerate() {
+            if row.len() != size {
+                return Err(MapError::NotSquare {
+                    row: i,
+                    len: row.len(),
+                    size,
+                });
+            }
+            for (j, &c) in row.iter().enumerate() {
+                if c == '*' || c == '.' {
+                    continue;
+                }
+                let code = c.to_string();
+                if PieceKind::from_code(&code).is_none() {
+                    return Err(MapError::UnknownCode {
+                        row: i,
+                        col: j,
+                        code: c,
+                    });
+                }
+                pieces.push((code, (i as i64, j as i64)));
+            }
+        }
+
+        let count = |code| pieces.iter().filter(|(c, _)| c == code).count();
+        let players = count("P");
+        if players != 1 {
+            return Err(MapError::PieceCount {
+                code: "P",
+                count: players,
+            });
+        }
+        if count("+") == 0 {
+            return Err(MapError::PieceCount {
+                code: "+",
+                count: 0,
+            });
+        }
+
+        Ok(GridMap {
+            size: size as i64,
+            pieces,
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<GridMap, MapError> {
+        GridMap::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MapError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    // pieces are listed row by row like `parse` reads them,
+    // overlapping ones by layer
+    pub fn from_board(board: &GridBoard) -> GridMap {
+        let mut pieces = board.ordered_pieces();
+        pieces.sort_by_key(|piece| (piece.pos, piece.layer));
+        GridMap {
+            size: board.size,
+            pieces: pieces
+                .iter()
+                .map(|piece| (piece.code.clone(), piece.pos))
+                .collect(),
+        }
+    }
+
+    // put the pieces on a board, keeping their map order,
+    // named like `GridWorld::add_piece` names them
+    pub fn place(&self, board: &mut GridBoard) {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for (code, pos) in self.pieces.iter() {
+            let count = counts.entry(code).or_insert(0);
+            *count += 1;
+            let name = match PieceKind::from_code(code) {
+                Some(kind) => kind.piece_name(*count),
+                None => code.clone(),
+            };
+            board.add_piece(name, code.clone(), *pos);
+        }
+    }
+}
+
+// overlapping pieces cannot be written out,
+// the first one in `pieces` wins the square
+impl fmt::Display for GridMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in 0..self.size {
+            let row: Vec<&str> = (0..self.size)
+                .map(|j| {
+                    self.pieces
+                        .iter()
+                        .find(|(_, pos)| *pos == (i, j))
+                        .map_or("*", |(code, _)| code.as_str())
+                })
+                .collect();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::grid_map::*;
+
+    #[test]
+    fn test_parse_map() {
+        let map = GridMap::parse(
+            "# two walls, two pits
+             P * * W
+             * W - *
+             . . . +
+             * - * *",
+        )
+        .unwrap();
+        assert_eq!(map.size, 4);
+        assert_eq!(map.pieces.len(), 6);
+        assert_eq!(map.pieces[0], (String::from("P"), (0, 0)));
+
+        let mut board = GridBoard::new(map.size);
+        map.place(&mut board);
+        assert!(board.components.contains_key("Wall2"));
+        assert!(board.components.contains_key("Pit2"));
+        assert_eq!(board.positions("W").len(), 2);
+        assert_eq!(GridMap::parse(&map.to_string()).unwrap(), map);
+    }
+
+    #[test]
+    fn test_parse_map_errors() {
+        assert!(matches!(GridMap::parse("# nothing"), Err(MapError::Empty)));
+        assert!(matches!(
+            GridMap::parse("P+\n*"),
+            Err(MapError::NotSquare { row: 1, .. })
+        ));
+        assert!(matches!(
+            GridMap::parse("P+\n*x"),
+            Err(MapError::UnknownCode { code: 'x', .. })
+        ));
+        assert!(matches!(
+            GridMap::parse("P+\nP*"),
+            Err(MapError::PieceCount {
+                code: "P",
+                count: 2
+            })
+        ));
+        assert!(GridMap::parse("P+\n*+").is_ok());
+        assert!(matches!(
+            GridMap::parse("P*\n**"),
+            Err(MapError::PieceCount {
+                code: "+",
+                count: 0
+            })
+        ));
+    }
+}
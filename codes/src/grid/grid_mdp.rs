@@ -0,0 +1,123 @@
+use crate::grid::grid_world::{Action, GameStatus, GridWorld};
+use crate::mdp::tabular_mdp::TabularMdp;
+use std::collections::{HashMap, VecDeque};
+
+// a `GridWorld` layout as an explicit MDP: the states are the
+// squares the player can reach from its starting square, the
+// actions are `Action::ALL`, and transitions follow the world's
+// dynamics. Goals and pits are terminal; the move limit is not
+// part of the state and is left out
+#[derive(Debug, Clone)]
+pub struct GridMdp {
+    pub mdp: TabularMdp,
+    // player position of every state, the start is state 0
+    pub states: Vec<(i64, i64)>,
+}
+
+impl GridMdp {
+    pub fn from_world(world: &GridWorld) -> GridMdp {
+        let mut world = world.clone();
+        world.max_moves = None;
+
+        let start = world.player_pos();
+        let mut states = vec![start];
+        let mut index: HashMap<(i64, i64), usize> = HashMap::new();
+        index.insert(start, 0);
+        // (state, action, next, probability, reward)
+        let mut transitions = vec![];
+        let mut terminal = vec![];
+
+        let mut queue = VecDeque::new();
+        queue.push_back(0);
+        while let Some(state) = queue.pop_front() {
+            let pos = states[state];
+            world.board.components.get_mut("Player").unwrap().pos = pos;
+            if world.status() != GameStatus::Running {
+                terminal.push(state);
+                continue;
+            }
+
+            for (a, &action) in Action::ALL.iter().enumerate() {
+                for (actual, probability) in world.dynamics.action_probabilities(action) {
+                    world.board.components.get_mut("Player").unwrap().pos = pos;
+                    world.apply_action(actual);
+                    let next_pos = world.player_pos();
+                    let next = *index.entry(next_pos).or_insert_with(|| {
+                        states.push(next_pos);
+                        queue.push_back(states.len() - 1);
+                        states.len() - 1
+                    });
+                    transitions.push((state, a, next, probability, world.reward()));
+                }
+            }
+        }
+
+        let mut mdp = TabularMdp::new(states.len(), Action::ALL.len());
+        for (state, action, next, probability, reward) in transitions {
+            mdp.add_transition(state, action, next, probability, reward);
+        }
+        for state in terminal {
+            mdp.set_terminal(state);
+        }
+        GridMdp { mdp, states }
+    }
+
+    pub fn state_index(&self, pos: (i64, i64)) -> Option<usize> {
+        self.states.iter().position(|&p| p == pos)
+    }
+}
+
+impl GridWorld {
+    // the current layout as an explicit MDP
+    pub fn to_mdp(&self) -> GridMdp {
+        GridMdp::from_world(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::grid_mdp::*;
+    use crate::grid::grid_world::{Dynamics, SlipMode};
+
+    #[test]
+    fn test_static_mdp() {
+        let world = GridWorld::new(4, String::from("static"));
+        let grid_mdp = world.to_mdp();
+        let mdp = &grid_mdp.mdp;
+        // every square but the wall
+        assert_eq!(mdp.state_count, 15);
+        assert_eq!(grid_mdp.states[0], (0, 3));
+        assert_eq!(grid_mdp.state_index((1, 1)), None);
+        assert_eq!(mdp.terminal.iter().filter(|&&t| t).count(), 2);
+        assert_eq!(mdp.find_unnormalized(1e-9), None);
+
+        // stepping left from next to the pit loses
+        let s = grid_mdp.state_index((0, 2)).unwrap();
+        let pit = grid_mdp.state_index((0, 1)).unwrap();
+        assert_eq!(mdp.probability(s, 2, pit), 1.0);
+        assert_eq!(mdp.expected_reward(s, 2), -10.0);
+        assert!(mdp.terminal[pit]);
+
+        // the exported world is left untouched
+        assert_eq!(world.player_pos(), (0, 3));
+        assert_eq!(world.moves(), 0);
+    }
+
+    #[test]
+    fn test_slippery_mdp() {
+        let world = GridWorld::new(4, String::from("static"))
+            .with_dynamics(Dynamics::slippery(0.2, SlipMode::Perpendicular));
+        let grid_mdp = world.to_mdp();
+        let mdp = &grid_mdp.mdp;
+        assert_eq!(mdp.find_unnormalized(1e-9), None);
+
+        // LEFT from (2, 2): mostly left, sometimes up or down
+        let s = grid_mdp.state_index((2, 2)).unwrap();
+        let left = grid_mdp.state_index((2, 1)).unwrap();
+        let up = grid_mdp.state_index((1, 2)).unwrap();
+        let down = grid_mdp.state_index((3, 2)).unwrap();
+        assert!((mdp.probability(s, 2, left) - 0.8).abs() < 1e-12);
+        assert!((mdp.probability(s, 2, up) - 0.1).abs() < 1e-12);
+        assert!((mdp.probability(s, 2, down) - 0.1).abs() < 1e-12);
+    }
+}
@@ -1,13 +1,179 @@
+use crate::env::environment::{Environment, Observation, StepResult};
 use crate::grid::grid_board::rand_pos;
-use crate::grid::grid_board::GridBoard;
+use crate::grid::grid_board::{GridBoard, PieceKind};
+use crate::grid::grid_map::{GridMap, MapError};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use std::collections::btree_map::BTreeMap;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::path::Path;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GridWorld {
     pub board: GridBoard,
+    // layout mode: "static", "player", "map" or anything else for random
+    pub mode: String,
+    // layout restored on reset in "map" mode
+    pub map: Option<GridMap>,
+    // pieces laid out by the random mode
+    pub piece_counts: PieceCounts,
+    pub rewards: Rewards,
+    // end the game as truncated after this many moves
+    pub max_moves: Option<usize>,
+    // slipping and wind, applied by `make_move`
+    pub dynamics: Dynamics,
+    // moves made since the board was laid out
+    moves: usize,
+    // source of all randomness in board layouts and transitions
+    rng: StdRng,
 }
 
-#[derive(Debug, Clone, Copy)]
+// how many pieces of each kind the random mode lays out,
+// on top of the single player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PieceCounts {
+    pub goals: usize,
+    pub pits: usize,
+    pub walls: usize,
+}
+
+impl Default for PieceCounts {
+    fn default() -> PieceCounts {
+        PieceCounts {
+            goals: 1,
+            pits: 1,
+            walls: 1,
+        }
+    }
+}
+
+// reward for the square the player ends a move on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rewards {
+    pub goal: f64,
+    pub pit: f64,
+    // any other square
+    pub step: f64,
+}
+
+impl Default for Rewards {
+    fn default() -> Rewards {
+        Rewards {
+            goal: 10.0,
+            pit: -10.0,
+            step: -1.0,
+        }
+    }
+}
+
+// what an action turns into when the player slips
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlipMode {
+    // one of the two moves at right angles to the intended one
+    Perpendicular,
+    // any of the four moves, the intended one included
+    Random,
+}
+
+// transition noise of a world; the default is deterministic
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dynamics {
+    // probability that the intended action is replaced
+    pub slip: f64,
+    pub slip_mode: SlipMode,
+    // (row, column) push the wind gives a player that ends
+    // a move on the square, one square at a time
+    pub wind: HashMap<(i64, i64), (i64, i64)>,
+}
+
+impl Default for Dynamics {
+    fn default() -> Dynamics {
+        Dynamics {
+            slip: 0.0,
+            slip_mode: SlipMode::Perpendicular,
+            wind: HashMap::new(),
+        }
+    }
+}
+
+impl Dynamics {
+    pub fn slippery(slip: f64, slip_mode: SlipMode) -> Dynamics {
+        assert!(
+            (0.0..=1.0).contains(&slip),
+            "slip probability {} is not in [0, 1]",
+            slip
+        );
+        Dynamics {
+            slip,
+            slip_mode,
+            ..Dynamics::default()
+        }
+    }
+
+    // wind blowing `strengths[j]` squares up on every square of
+    // column j, as in the windy gridworld of Sutton and Barto
+    pub fn windy_columns(mut self, size: i64, strengths: &[i64]) -> Dynamics {
+        for (j, &strength) in strengths.iter().enumerate() {
+            if strength == 0 {
+                continue;
+            }
+            for i in 0..size {
+                self.wind.insert((i, j as i64), (-strength, 0));
+            }
+        }
+        self
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.slip == 0.0 && self.wind.is_empty()
+    }
+
+    // actions actually carried out when `action` is chosen,
+    // with their probabilities
+    pub fn action_probabilities(&self, action: Action) -> Vec<(Action, f64)> {
+        let others: Vec<Action> = match self.slip_mode {
+            SlipMode::Perpendicular => action.perpendicular().to_vec(),
+            SlipMode::Random => Action::ALL.to_vec(),
+        };
+        let share = self.slip / others.len() as f64;
+
+        let mut probabilities = vec![(action, 1.0 - self.slip)];
+        for other in others {
+            match probabilities.iter_mut().find(|(a, _)| *a == other) {
+                Some((_, p)) => *p += share,
+                None => probabilities.push((other, share)),
+            }
+        }
+        probabilities.retain(|&(_, p)| p > 0.0);
+        probabilities
+    }
+}
+
+// result of trying to move a piece one square
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveOutcome {
+    // the square is free (or the goal), the piece can move there
+    Valid,
+    // blocked by a wall or the edge of the board, the piece stays put
+    Invalid,
+    // the square is a pit, moving there loses the game
+    Lost,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    Running,
+    // the player reached a goal
+    Won,
+    // the player fell into a pit
+    Lost,
+    // the move limit ran out before the game was decided
+    Truncated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Action {
     UP,
     DOWN,
@@ -15,185 +181,477 @@ pub enum Action {
     RIGHT,
 }
 
+impl Action {
+    // all actions, in action index order
+    pub const ALL: [Action; 4] = [Action::UP, Action::DOWN, Action::LEFT, Action::RIGHT];
+
+    pub fn from_index(index: usize) -> Action {
+        match Action::ALL.get(index) {
+            Some(&action) => action,
+            None => panic!("invalid action index: {}", index),
+        }
+    }
+
+    // (row, column) offset of a move
+    pub fn direction(&self) -> (i64, i64) {
+        match self {
+            Action::UP => (-1, 0),
+            Action::DOWN => (1, 0),
+            Action::LEFT => (0, -1),
+            Action::RIGHT => (0, 1),
+        }
+    }
+
+    // the two moves at right angles to this one
+    pub fn perpendicular(&self) -> [Action; 2] {
+        match self {
+            Action::UP | Action::DOWN => [Action::LEFT, Action::RIGHT],
+            Action::LEFT | Action::RIGHT => [Action::UP, Action::DOWN],
+        }
+    }
+}
+
+// why a board can't be played
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    // the named piece lies outside the board
+    OutOfBounds(String),
+    // more than one piece on this square
+    Overlap((i64, i64)),
+    // walls and pits cut the player off from every goal
+    Unreachable,
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardError::OutOfBounds(name) => write!(f, "{} is outside the board", name),
+            BoardError::Overlap(pos) => write!(f, "more than one piece at {:?}", pos),
+            BoardError::Unreachable => write!(f, "no goal can be reached from the player"),
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
 impl GridWorld {
+    // piece codes of the observation planes: Player, Goal, Pit, Wall,
+    // see `PieceKind::ALL`
+    pub const LAYOUT: [&'static str; 4] = ["P", "+", "-", "W"];
+
+    // world with layouts drawn from an entropy-seeded rng
     pub fn new(size: i64, mode: String) -> GridWorld {
+        GridWorld::with_rng(size, mode, StdRng::from_entropy())
+    }
+
+    // world whose layouts are fully determined by `seed`
+    pub fn with_seed(size: i64, mode: String, seed: u64) -> GridWorld {
+        GridWorld::with_rng(size, mode, StdRng::seed_from_u64(seed))
+    }
+
+    pub fn with_rng(size: i64, mode: String, rng: StdRng) -> GridWorld {
         let mut actual_size = size;
         if size < 4 {
             actual_size = 4;
         }
 
         let mut world = GridWorld {
-            board: GridBoard::new(actual_size),
+            board: GridBoard::with_layout(actual_size, &GridWorld::LAYOUT),
+            mode,
+            map: None,
+            piece_counts: PieceCounts::default(),
+            rewards: Rewards::default(),
+            max_moves: None,
+            dynamics: Dynamics::default(),
+            moves: 0,
+            rng,
         };
+        world.init_grid();
+        world
+    }
+
+    // world that always starts from the given map, at the map's size
+    pub fn from_map(map: GridMap) -> GridWorld {
+        GridWorld::from_map_with_rng(map, StdRng::from_entropy())
+    }
+
+    // map world whose transitions are fully determined by `seed`
+    pub fn from_map_with_seed(map: GridMap, seed: u64) -> GridWorld {
+        GridWorld::from_map_with_rng(map, StdRng::seed_from_u64(seed))
+    }
+
+    pub fn from_map_with_rng(map: GridMap, rng: StdRng) -> GridWorld {
+        let mut world = GridWorld {
+            board: GridBoard::with_layout(map.size, &GridWorld::LAYOUT),
+            mode: String::from("map"),
+            map: Some(map),
+            piece_counts: PieceCounts::default(),
+            rewards: Rewards::default(),
+            max_moves: None,
+            dynamics: Dynamics::default(),
+            moves: 0,
+            rng,
+        };
+        world.init_grid();
+        world
+    }
+
+    // the same world with slipping or wind, e.g.
+    // `GridWorld::with_seed(4, mode, seed).with_dynamics(Dynamics::slippery(0.1, SlipMode::Random))`
+    pub fn with_dynamics(mut self, dynamics: Dynamics) -> GridWorld {
+        self.dynamics = dynamics;
+        self
+    }
+
+    pub fn load_map<P: AsRef<Path>>(path: P) -> Result<GridWorld, MapError> {
+        Ok(GridWorld::from_map(GridMap::load(path)?))
+    }
+
+    pub fn load_map_with_seed<P: AsRef<Path>>(path: P, seed: u64) -> Result<GridWorld, MapError> {
+        Ok(GridWorld::from_map_with_seed(GridMap::load(path)?, seed))
+    }
+
+    // the current board as a map
+    pub fn to_map(&self) -> GridMap {
+        GridMap::from_board(&self.board)
+    }
+
+    pub fn save_map<P: AsRef<Path>>(&self, path: P) -> Result<(), MapError> {
+        self.to_map().save(path)
+    }
 
-        if mode == "static" {
-            world.init_grid_static()
-        } else if mode == "player" {
-            world.init_grid_player();
+    // place the pieces according to the layout mode
+    fn init_grid(&mut self) {
+        if self.mode == "static" {
+            self.init_grid_static()
+        } else if self.mode == "map" {
+            self.init_grid_map();
+        } else if self.mode == "player" {
+            self.init_grid_player();
         } else {
-            world.init_grid_rand();
+            self.init_grid_rand();
         }
+    }
 
-        world
+    // add a piece, named after how many of its kind are on the board
+    pub fn add_piece(&mut self, kind: PieceKind, pos: (i64, i64)) {
+        let name = kind.piece_name(self.positions(kind).len() + 1);
+        self.board.add_piece(name, String::from(kind.code()), pos);
+    }
+
+    // squares of all pieces of a kind
+    pub fn positions(&self, kind: PieceKind) -> Vec<(i64, i64)> {
+        self.board.positions(kind.code())
+    }
+
+    pub fn player_pos(&self) -> (i64, i64) {
+        self.board.components.get("Player").unwrap().pos
     }
 
     pub fn init_grid_static(&mut self) {
         // add pieces
-        self.board
-            .add_piece(String::from("Player"), String::from("P"), (0, 3));
-        self.board
-            .add_piece(String::from("Goal"), String::from("+"), (0, 0));
-        self.board
-            .add_piece(String::from("Pit"), String::from("-"), (0, 1));
-        self.board
-            .add_piece(String::from("Wall"), String::from("W"), (1, 1));
-    }
-
-    pub fn validate_move(&self, piece: String, move_direction: (i64, i64)) -> i64 {
-        // 0 is valid, 1 invalid, 2 lost game
-        let mut _outcome = 0;
-
-        let pit = self.board.components.get("Pit").unwrap().pos;
-        let wall = self.board.components.get("Wall").unwrap().pos;
+        self.add_piece(PieceKind::Player, (0, 3));
+        self.add_piece(PieceKind::Goal, (0, 0));
+        self.add_piece(PieceKind::Pit, (0, 1));
+        self.add_piece(PieceKind::Wall, (1, 1));
+    }
+
+    pub fn init_grid_map(&mut self) {
+        if let Some(map) = self.map.as_ref() {
+            map.place(&mut self.board);
+        }
+    }
+
+    pub fn validate_move(&self, piece: String, move_direction: (i64, i64)) -> MoveOutcome {
+        let pits = self.positions(PieceKind::Pit);
+        let walls = self.positions(PieceKind::Wall);
         let target = self.board.components.get(&piece).unwrap().pos;
         let new_pos = (target.0 + move_direction.0, target.1 + move_direction.1);
 
-        if new_pos == wall {
-            1
-        } else if new_pos == pit {
-            2
+        if walls.contains(&new_pos) {
+            MoveOutcome::Invalid
+        } else if pits.contains(&new_pos) {
+            MoveOutcome::Lost
         } else if new_pos.0 >= 0
-            && new_pos.0 <= self.board.size - 1
+            && new_pos.0 < self.board.size
             && new_pos.1 >= 0
-            && new_pos.1 <= self.board.size - 1
+            && new_pos.1 < self.board.size
         {
-            0
+            MoveOutcome::Valid
         } else {
-            1
+            MoveOutcome::Invalid
         }
     }
 
+    // a board is valid when no two pieces share a square
+    // and a goal can be reached from the player's square
     pub fn validate_board(&self) -> bool {
-        let mut valid = true;
+        self.check_board().is_ok()
+    }
 
-        // ensure no duplicate positions
+    // check the board and return the length of the shortest
+    // path from the player to the nearest goal
+    pub fn check_board(&self) -> Result<usize, BoardError> {
         let mut all_position = BTreeMap::new();
-        for c in self.board.components.iter() {
-            *all_position.entry(c.1.pos).or_insert(0) += 1;
-        }
-        if all_position.len() < 4 {
-            valid = false;
-        }
-
-        // ensure not dead position
-        let player = self.board.components.get("Player").unwrap().pos;
-        let goal = self.board.components.get("Goal").unwrap().pos;
-        let corner_positions: Vec<(i64, i64)> = vec![
-            (0, 0),
-            (0, self.board.size - 1),
-            (self.board.size - 1, 0),
-            (self.board.size - 1, self.board.size - 1),
-        ];
-        let move_directions: Vec<(i64, i64)> = vec![(0, 1), (-1, 0), (1, 0), (-1, 0)];
-
-        if corner_positions.iter().any(|&x| x == player) {
-            let move_status: Vec<i64> = move_directions
-                .iter()
-                .map(|&x| self.validate_move(String::from("Player"), x))
-                .collect();
-            if !move_status.iter().any(|&x| x == 0) {
-                valid = false
+        for piece in self.board.components.values() {
+            let (i, j) = piece.pos;
+            if i < 0 || i >= self.board.size || j < 0 || j >= self.board.size {
+                return Err(BoardError::OutOfBounds(piece.name.clone()));
             }
+            *all_position.entry(piece.pos).or_insert(0) += 1;
+        }
+        if let Some((&pos, _)) = all_position.iter().find(|(_, &count)| count > 1) {
+            return Err(BoardError::Overlap(pos));
         }
 
-        if corner_positions.iter().any(|&x| x == goal) {
-            let move_status: Vec<i64> = move_directions
-                .iter()
-                .map(|&x| self.validate_move(String::from("Goal"), x))
-                .collect();
-            if !move_status.iter().any(|&x| x == 0) {
-                valid = false
+        self.shortest_path().ok_or(BoardError::Unreachable)
+    }
+
+    // fewest moves that take the player from its current square
+    // to a goal without crossing walls or pits, found by
+    // breadth-first search; None if no goal can be reached
+    pub fn shortest_path(&self) -> Option<usize> {
+        let start = self.board.components.get("Player")?.pos;
+        let goals = self.positions(PieceKind::Goal);
+        let walls = self.positions(PieceKind::Wall);
+        let pits = self.positions(PieceKind::Pit);
+
+        let mut visited = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(start);
+        queue.push_back((start, 0));
+        while let Some((pos, distance)) = queue.pop_front() {
+            if goals.contains(&pos) {
+                return Some(distance);
+            }
+            for action in Action::ALL.iter() {
+                let (di, dj) = action.direction();
+                let next = (pos.0 + di, pos.1 + dj);
+                if next.0 < 0
+                    || next.0 >= self.board.size
+                    || next.1 < 0
+                    || next.1 >= self.board.size
+                    || walls.contains(&next)
+                    || pits.contains(&next)
+                {
+                    continue;
+                }
+                if visited.insert(next) {
+                    queue.push_back((next, distance + 1));
+                }
             }
         }
-
-        valid
+        None
     }
 
+    // the static layout with the player moved to a random square
     pub fn init_grid_player(&mut self) {
         self.init_grid_static();
-        let mut player = self.board.components.get_mut("Player").unwrap();
-        (*player).pos = rand_pos(0, self.board.size);
+        loop {
+            let pos = rand_pos(&mut self.rng, 0, self.board.size);
+            self.board.components.get_mut("Player").unwrap().pos = pos;
 
-        if !self.validate_board() {
-            self.init_grid_player();
+            if self.validate_board() {
+                break;
+            }
         }
     }
 
+    // lay out `piece_counts` pieces on distinct random squares,
+    // retrying until the board is solvable
     pub fn init_grid_rand(&mut self) {
-        self.board.add_piece(
-            String::from("Player"),
-            String::from("P"),
-            rand_pos(0, self.board.size),
-        );
-        self.board.add_piece(
-            String::from("Goal"),
-            String::from("+"),
-            rand_pos(0, self.board.size),
-        );
-        self.board.add_piece(
-            String::from("Pit"),
-            String::from("-"),
-            rand_pos(0, self.board.size),
-        );
-        self.board.add_piece(
-            String::from("Wall"),
-            String::from("W"),
-            rand_pos(0, self.board.size),
+        let counts = self.piece_counts;
+        let size = self.board.size;
+        let total = 1 + counts.goals + counts.pits + counts.walls;
+        assert!(
+            total as i64 <= size * size,
+            "{} pieces don't fit on a {}x{} board",
+            total,
+            size,
+            size
         );
 
-        if !self.validate_board() {
-            self.init_grid_rand();
+        let mut squares: Vec<(i64, i64)> = (0..size)
+            .flat_map(|i| (0..size).map(move |j| (i, j)))
+            .collect();
+        for _ in 0..10000 {
+            self.board = GridBoard::with_layout(size, &GridWorld::LAYOUT);
+            squares.shuffle(&mut self.rng);
+            let mut free = squares.iter().copied();
+            self.add_piece(PieceKind::Player, free.next().unwrap());
+            for _ in 0..counts.goals {
+                self.add_piece(PieceKind::Goal, free.next().unwrap());
+            }
+            for _ in 0..counts.pits {
+                self.add_piece(PieceKind::Pit, free.next().unwrap());
+            }
+            for _ in 0..counts.walls {
+                self.add_piece(PieceKind::Wall, free.next().unwrap());
+            }
+
+            if self.validate_board() {
+                return;
+            }
         }
+        panic!("no solvable {}x{} board with {:?}", size, size, counts);
+    }
+
+    // piece codes of the observation planes, so a saved model
+    // can check it is fed the planes it was trained on
+    pub fn layout(&self) -> Vec<String> {
+        self.board.layout()
     }
 
     pub fn display(&self) {
         self.board.render();
     }
 
-    pub fn make_move(&mut self, action: Action) {
-        let move_direction: (i64, i64);
-        match action {
-            Action::UP => move_direction = (-1, 0),
-            Action::DOWN => move_direction = (1, 0),
-            Action::LEFT => move_direction = (0, -1),
-            Action::RIGHT => move_direction = (0, 1),
+    // move the player; once the game is decided or truncated
+    // the board is frozen and every move is invalid.
+    // With slipping, the action carried out is drawn from
+    // `Dynamics::action_probabilities`
+    pub fn make_move(&mut self, action: Action) -> MoveOutcome {
+        if self.status() != GameStatus::Running {
+            return MoveOutcome::Invalid;
+        }
+        self.moves += 1;
+
+        let action = if self.dynamics.slip > 0.0 {
+            self.sample_action(action)
+        } else {
+            action
+        };
+        self.apply_action(action)
+    }
+
+    fn sample_action(&mut self, action: Action) -> Action {
+        let probabilities = self.dynamics.action_probabilities(action);
+        let mut u: f64 = self.rng.gen_range(0.0..1.0);
+        for &(a, p) in probabilities.iter() {
+            if u < p {
+                return a;
+            }
+            u -= p;
         }
+        probabilities.last().unwrap().0
+    }
 
-        let move_status = self.validate_move(String::from("Player"), move_direction);
-        if move_status == 0 || move_status == 2 {
-            let mut player = self.board.components.get_mut("Player").unwrap();
-            (*player).pos = (
-                (*player).pos.0 + move_direction.0,
-                (*player).pos.1 + move_direction.1,
-            );
+    // carry out exactly this action, then let the wind on the
+    // square the player ends on push it; neither counts a move.
+    // The outcome is Lost if either ends in a pit, otherwise
+    // that of the action itself
+    pub fn apply_action(&mut self, action: Action) -> MoveOutcome {
+        let outcome = self.step_player(action.direction());
+        if self.on_goal_or_pit() {
+            return outcome;
+        }
+
+        if let Some(&(di, dj)) = self.dynamics.wind.get(&self.player_pos()) {
+            let pushes = (0..di.abs())
+                .map(|_| (di.signum(), 0))
+                .chain((0..dj.abs()).map(|_| (0, dj.signum())));
+            for push in pushes {
+                if self.step_player(push) == MoveOutcome::Lost {
+                    return MoveOutcome::Lost;
+                }
+                if self.on_goal_or_pit() {
+                    break;
+                }
+            }
+        }
+        outcome
+    }
+
+    // the game is decided, whatever the move limit says
+    fn on_goal_or_pit(&self) -> bool {
+        let player_pos = self.player_pos();
+        self.positions(PieceKind::Goal).contains(&player_pos)
+            || self.positions(PieceKind::Pit).contains(&player_pos)
+    }
+
+    // move the player one square unless it is blocked
+    fn step_player(&mut self, direction: (i64, i64)) -> MoveOutcome {
+        let outcome = self.validate_move(String::from("Player"), direction);
+        if outcome != MoveOutcome::Invalid {
+            let player = self.board.components.get_mut("Player").unwrap();
+            player.pos = (player.pos.0 + direction.0, player.pos.1 + direction.1);
+        }
+        outcome
+    }
+
+    // moves made since the board was laid out
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    pub fn status(&self) -> GameStatus {
+        let player_pos = self.player_pos();
+        if self.positions(PieceKind::Pit).contains(&player_pos) {
+            GameStatus::Lost
+        } else if self.positions(PieceKind::Goal).contains(&player_pos) {
+            GameStatus::Won
+        } else if self.max_moves.is_some_and(|max| self.moves >= max) {
+            GameStatus::Truncated
+        } else {
+            GameStatus::Running
         }
     }
 
     pub fn reward(&self) -> f64 {
-        let pit_pos = self.board.components.get("Pit").unwrap().pos;
-        let goal_pos = self.board.components.get("Goal").unwrap().pos;
-        let player_pos = self.board.components.get("Player").unwrap().pos;
-        if pit_pos == player_pos {
-            -10.0
-        } else if goal_pos == player_pos {
-            10.0
+        let player_pos = self.player_pos();
+        if self.positions(PieceKind::Pit).contains(&player_pos) {
+            self.rewards.pit
+        } else if self.positions(PieceKind::Goal).contains(&player_pos) {
+            self.rewards.goal
         } else {
-            -1.0
+            self.rewards.step
+        }
+    }
+}
+
+impl Environment for GridWorld {
+    fn action_count(&self) -> usize {
+        Action::ALL.len()
+    }
+
+    fn observation_size(&self) -> usize {
+        (self.board.size * self.board.size) as usize * self.board.layer_count()
+    }
+
+    fn reset(&mut self, seed: Option<u64>) -> Observation {
+        if let Some(seed) = seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+        // start over with a fresh board in the same mode
+        self.board = GridBoard::with_layout(self.board.size, &GridWorld::LAYOUT);
+        self.init_grid();
+        self.moves = 0;
+        self.board.render_array()
+    }
+
+    fn step(&mut self, action: usize) -> StepResult {
+        self.make_move(Action::from_index(action));
+        let status = self.status();
+
+        let mut info = HashMap::new();
+        info.insert(String::from("moves"), self.moves as f64);
+        StepResult {
+            observation: self.board.render_array(),
+            reward: self.reward(),
+            terminated: status == GameStatus::Won || status == GameStatus::Lost,
+            truncated: status == GameStatus::Truncated,
+            info,
         }
     }
 }
 
 #[cfg(test)]
+// the board validation tests predate these lints
+#[allow(
+    unused_mut,
+    clippy::bool_assert_comparison,
+    clippy::explicit_auto_deref
+)]
 mod tests {
     use crate::grid::grid_world::*;
 
@@ -218,6 +676,105 @@ mod tests {
         assert_eq!(world.validate_board(), true);
     }
 
+    #[test]
+    fn test_layout() {
+        let codes: Vec<&str> = PieceKind::ALL.iter().map(|kind| kind.code()).collect();
+        assert_eq!(codes, GridWorld::LAYOUT);
+
+        for mode in ["static", "player", "random"].iter() {
+            let mut world = GridWorld::with_seed(4, String::from(*mode), 1);
+            assert_eq!(world.layout(), GridWorld::LAYOUT);
+            world.reset(None);
+            assert_eq!(world.layout(), GridWorld::LAYOUT);
+
+            // planes are Player, Goal, Pit, Wall
+            let observation = world.board.render_array();
+            for (layer, name) in ["Player", "Goal", "Pit", "Wall"].iter().enumerate() {
+                let pos = world.board.components.get(*name).unwrap().pos;
+                assert_eq!(observation[layer * 16 + (pos.0 * 4 + pos.1) as usize], 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_map_world() {
+        let map = GridMap::parse(
+            "P W * *
+             * - * *
+             * W - *
+             * * * +",
+        )
+        .unwrap();
+        let mut world = GridWorld::from_map(map.clone());
+        assert_eq!(world.layout(), GridWorld::LAYOUT);
+        assert_eq!(world.to_map(), map);
+
+        assert_eq!(world.make_move(Action::RIGHT), MoveOutcome::Invalid);
+        assert_eq!(world.make_move(Action::DOWN), MoveOutcome::Valid);
+        assert_eq!(world.make_move(Action::RIGHT), MoveOutcome::Lost);
+        assert_eq!(world.reward(), -10.0);
+
+        // reset goes back to the map layout
+        world.reset(None);
+        assert_eq!(world.to_map(), map);
+        assert_eq!(world.status(), GameStatus::Running);
+
+        // a map without pits keeps the standard planes
+        let mut world = GridWorld::from_map(GridMap::parse("P*\n*+").unwrap());
+        assert_eq!(world.layout(), GridWorld::LAYOUT);
+        assert_eq!(world.observation_size(), 16);
+        assert_eq!(world.step(1).reward, -1.0);
+        assert!(world.step(3).terminated);
+        assert_eq!(world.status(), GameStatus::Won);
+    }
+
+    #[test]
+    fn test_map_files() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/maps");
+        let static_world = GridWorld::load_map(format!("{}/static.txt", dir)).unwrap();
+        let world = GridWorld::new(4, String::from("static"));
+        assert_eq!(static_world.to_map(), world.to_map());
+
+        let maze = GridWorld::load_map(format!("{}/maze.txt", dir)).unwrap();
+        assert!(maze.board.positions("W").len() > 1);
+        assert!(maze.board.positions("-").len() > 1);
+
+        let path = std::env::temp_dir().join("drl_test_map_files.txt");
+        maze.save_map(&path).unwrap();
+        assert_eq!(GridWorld::load_map(&path).unwrap().to_map(), maze.to_map());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_seeded_layouts() {
+        let positions = |world: &GridWorld| {
+            let mut positions: Vec<(String, (i64, i64))> = world
+                .board
+                .components
+                .iter()
+                .map(|(name, piece)| (name.clone(), piece.pos))
+                .collect();
+            positions.sort();
+            positions
+        };
+
+        let mut a = GridWorld::with_seed(5, String::from("random"), 7);
+        let mut b = GridWorld::with_seed(5, String::from("random"), 7);
+        assert_eq!(positions(&a), positions(&b));
+
+        for _ in 0..10 {
+            a.reset(None);
+            b.reset(None);
+            assert_eq!(positions(&a), positions(&b));
+        }
+
+        a.reset(Some(3));
+        let first = positions(&a);
+        a.reset(None);
+        a.reset(Some(3));
+        assert_eq!(positions(&a), first);
+    }
+
     #[test]
     fn test_move() {
         let mut world = GridWorld::new(4, String::from("static"));
@@ -237,6 +794,268 @@ mod tests {
         println!("{:?}", world.board.render_array());
     }
 
+    #[test]
+    fn test_status() {
+        let mut world = GridWorld::new(4, String::from("static"));
+        assert_eq!(world.status(), GameStatus::Running);
+
+        // walk around the wall into the goal
+        assert_eq!(world.make_move(Action::UP), MoveOutcome::Invalid);
+        for &action in [Action::DOWN, Action::DOWN, Action::LEFT, Action::LEFT].iter() {
+            assert_eq!(world.make_move(action), MoveOutcome::Valid);
+            assert_eq!(world.status(), GameStatus::Running);
+        }
+        assert_eq!(world.make_move(Action::LEFT), MoveOutcome::Valid);
+        assert_eq!(world.make_move(Action::UP), MoveOutcome::Valid);
+        assert_eq!(world.make_move(Action::UP), MoveOutcome::Valid);
+        assert_eq!(world.status(), GameStatus::Won);
+        assert_eq!(world.moves(), 8);
+
+        // the finished game ignores further moves
+        assert_eq!(world.make_move(Action::RIGHT), MoveOutcome::Invalid);
+        assert_eq!(world.status(), GameStatus::Won);
+
+        world.reset(None);
+        assert_eq!(world.make_move(Action::LEFT), MoveOutcome::Valid);
+        assert_eq!(world.make_move(Action::LEFT), MoveOutcome::Lost);
+        assert_eq!(world.status(), GameStatus::Lost);
+    }
+
+    #[test]
+    fn test_move_limit() {
+        let mut world = GridWorld::new(4, String::from("static"));
+        world.max_moves = Some(3);
+        world.reset(None);
+
+        assert!(!world.step(1).done());
+        assert!(!world.step(0).done());
+        let step = world.step(1);
+        assert!(step.truncated);
+        assert!(!step.terminated);
+        assert_eq!(world.status(), GameStatus::Truncated);
+        assert_eq!(step.info["moves"], 3.0);
+
+        world.reset(None);
+        assert_eq!(world.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn test_environment_step() {
+        let mut world = GridWorld::new(4, String::from("static"));
+        let observation = world.reset(None);
+        assert_eq!(observation.len(), world.observation_size());
+        assert_eq!(world.action_count(), 4);
+
+        let step = world.step(2);
+        assert_eq!(step.reward, -1.0);
+        assert!(!step.done());
+
+        let step = world.step(2);
+        assert_eq!(step.reward, -10.0);
+        assert!(step.terminated);
+    }
+
+    #[test]
+    fn test_slip_probabilities() {
+        let still = Dynamics::default();
+        assert_eq!(
+            still.action_probabilities(Action::UP),
+            vec![(Action::UP, 1.0)]
+        );
+
+        let perpendicular = Dynamics::slippery(0.2, SlipMode::Perpendicular);
+        let probabilities = perpendicular.action_probabilities(Action::UP);
+        assert_eq!(probabilities.len(), 3);
+        assert!((probabilities[0].1 - 0.8).abs() < 1e-12);
+        assert!(!probabilities.iter().any(|&(a, _)| a == Action::DOWN));
+
+        let random = Dynamics::slippery(0.2, SlipMode::Random);
+        let probabilities = random.action_probabilities(Action::LEFT);
+        assert_eq!(probabilities.len(), 4);
+        assert_eq!(probabilities[0].0, Action::LEFT);
+        assert!((probabilities[0].1 - 0.85).abs() < 1e-12);
+        let total: f64 = probabilities.iter().map(|&(_, p)| p).sum();
+        assert!((total - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_slippery_moves() {
+        // always slipping sideways, so LEFT from the top edge
+        // either bumps into the edge or goes down
+        let dynamics = Dynamics::slippery(1.0, SlipMode::Perpendicular);
+        let mut world = GridWorld::with_seed(4, String::from("static"), 0).with_dynamics(dynamics);
+        for _ in 0..20 {
+            world.reset(None);
+            let outcome = world.make_move(Action::LEFT);
+            assert_ne!(world.player_pos(), (0, 2));
+            match outcome {
+                MoveOutcome::Invalid => assert_eq!(world.player_pos(), (0, 3)),
+                _ => assert_eq!(world.player_pos(), (1, 3)),
+            }
+        }
+
+        let walk = |seed| {
+            let dynamics = Dynamics::slippery(0.5, SlipMode::Random);
+            let mut world =
+                GridWorld::with_seed(4, String::from("static"), seed).with_dynamics(dynamics);
+            (0..10)
+                .map(|i| {
+                    world.step(i % 4);
+                    world.player_pos()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(walk(5), walk(5));
+    }
+
+    #[test]
+    fn test_seeded_map_world() {
+        let walk = |seed| {
+            let map = GridMap::parse("P***\n****\n****\n***+").unwrap();
+            let dynamics = Dynamics::slippery(0.5, SlipMode::Random);
+            let mut world = GridWorld::from_map_with_seed(map, seed).with_dynamics(dynamics);
+            (0..10)
+                .map(|i| {
+                    world.step(i % 4);
+                    world.player_pos()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(walk(5), walk(5));
+
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/maps");
+        let path = format!("{}/maze.txt", dir);
+        let a = GridWorld::load_map_with_seed(&path, 1).unwrap();
+        let b = GridWorld::load_map_with_seed(&path, 1).unwrap();
+        assert_eq!(a.to_map(), b.to_map());
+    }
+
+    #[test]
+    fn test_wind() {
+        let wind = Dynamics::default().windy_columns(4, &[0, 0, 1]);
+        assert!(!wind.is_deterministic());
+        let mut world = GridWorld::new(4, String::from("static")).with_dynamics(wind);
+        assert_eq!(world.make_move(Action::DOWN), MoveOutcome::Valid);
+        assert_eq!(world.make_move(Action::DOWN), MoveOutcome::Valid);
+        // stepping into column 2 blows the player back up a row
+        assert_eq!(world.make_move(Action::LEFT), MoveOutcome::Valid);
+        assert_eq!(world.player_pos(), (1, 2));
+        // the wind still blows when the wall blocks the move
+        assert_eq!(world.make_move(Action::LEFT), MoveOutcome::Invalid);
+        assert_eq!(world.player_pos(), (0, 2));
+        assert_eq!(world.moves(), 4);
+
+        // a gust into the pit loses the game
+        world.reset(None);
+        world.dynamics.wind.insert((0, 2), (0, -1));
+        assert_eq!(world.make_move(Action::LEFT), MoveOutcome::Lost);
+        assert_eq!(world.status(), GameStatus::Lost);
+    }
+
+    #[test]
+    fn test_check_board() {
+        let world = GridWorld::new(4, String::from("static"));
+        // under the pit and the wall: down x2, left x3, up x2
+        assert_eq!(world.check_board(), Ok(7));
+
+        let maze = GridWorld::from_map(
+            GridMap::parse(
+                "P * W *
+                 - * W *
+                 * * * *
+                 * W - +",
+            )
+            .unwrap(),
+        );
+        assert_eq!(maze.shortest_path(), Some(6));
+
+        let blocked = GridWorld::from_map(
+            GridMap::parse(
+                "P * W *
+                 * * W *
+                 - - - *
+                 * * * +",
+            )
+            .unwrap(),
+        );
+        assert_eq!(blocked.check_board(), Err(BoardError::Unreachable));
+
+        let mut world = GridWorld::new(4, String::from("static"));
+        world.board.components.get_mut("Wall").unwrap().pos = (0, 0);
+        assert_eq!(world.check_board(), Err(BoardError::Overlap((0, 0))));
+        world.board.components.get_mut("Wall").unwrap().pos = (4, 0);
+        assert_eq!(
+            world.check_board(),
+            Err(BoardError::OutOfBounds(String::from("Wall")))
+        );
+    }
+
+    #[test]
+    fn test_many_pieces() {
+        let mut world = GridWorld::from_map(
+            GridMap::parse(
+                "+ * W *
+                 * P W -
+                 - * * *
+                 W * - +",
+            )
+            .unwrap(),
+        );
+        assert_eq!(world.positions(PieceKind::Goal).len(), 2);
+        assert_eq!(world.shortest_path(), Some(2));
+        let observation = world.board.render_array();
+        assert_eq!(observation[16..32].iter().sum::<f64>(), 2.0);
+        assert_eq!(observation[32..48].iter().sum::<f64>(), 3.0);
+        assert_eq!(observation[48..64].iter().sum::<f64>(), 3.0);
+
+        world.rewards = Rewards {
+            goal: 1.0,
+            pit: -1.0,
+            step: 0.0,
+        };
+        assert_eq!(world.make_move(Action::RIGHT), MoveOutcome::Invalid);
+        assert_eq!(world.reward(), 0.0);
+        world.make_move(Action::DOWN);
+        world.make_move(Action::DOWN);
+        assert_eq!(world.make_move(Action::RIGHT), MoveOutcome::Lost);
+        assert_eq!(world.reward(), -1.0);
+
+        world.reset(None);
+        world.make_move(Action::UP);
+        world.make_move(Action::LEFT);
+        assert_eq!(world.status(), GameStatus::Won);
+        assert_eq!(world.reward(), 1.0);
+    }
+
+    #[test]
+    fn test_piece_counts() {
+        let mut world = GridWorld::with_seed(6, String::from("random"), 3);
+        world.piece_counts = PieceCounts {
+            goals: 2,
+            pits: 4,
+            walls: 6,
+        };
+        for _ in 0..20 {
+            world.reset(None);
+            assert_eq!(world.positions(PieceKind::Goal).len(), 2);
+            assert_eq!(world.positions(PieceKind::Pit).len(), 4);
+            assert_eq!(world.positions(PieceKind::Wall).len(), 6);
+            assert!(world.check_board().is_ok());
+            assert_eq!(world.layout(), GridWorld::LAYOUT);
+        }
+    }
+
+    #[test]
+    fn test_generated_boards_solvable() {
+        for mode in ["player", "random"].iter() {
+            let mut world = GridWorld::with_seed(4, String::from(*mode), 5);
+            for _ in 0..100 {
+                world.reset(None);
+                assert!(world.check_board().is_ok());
+            }
+        }
+    }
+
     #[test]
     fn test_crate_validate_board0() {
         let mut world = GridWorld::new(5, String::from("static"));
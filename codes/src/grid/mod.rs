@@ -1,2 +1,4 @@
 pub mod grid_board;
+pub mod grid_map;
+pub mod grid_mdp;
 pub mod grid_world;
\ No newline at end of file
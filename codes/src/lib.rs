@@ -1,2 +1,8 @@
+pub mod agent;
+pub mod bandit;
+pub mod env;
 pub mod grid;
-pub mod plot;
\ No newline at end of file
+pub mod mdp;
+pub mod planning;
+pub mod plot;
+pub mod util;
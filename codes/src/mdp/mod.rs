@@ -0,0 +1 @@
+pub mod tabular_mdp;
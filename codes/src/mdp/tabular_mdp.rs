@@ -0,0 +1,150 @@
+// one possible result of taking an action
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    pub next: usize,
+    pub probability: f64,
+    pub reward: f64,
+}
+
+// finite Markov decision process with states and actions
+// numbered from 0, stored sparsely: only transitions with
+// a non-zero probability are kept. Terminal states loop
+// back to themselves with reward 0 under every action
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabularMdp {
+    pub state_count: usize,
+    pub action_count: usize,
+    // transitions[s][a]: possible results of action a in state s
+    pub transitions: Vec<Vec<Vec<Transition>>>,
+    pub terminal: Vec<bool>,
+}
+
+impl TabularMdp {
+    // MDP without any transitions yet
+    pub fn new(state_count: usize, action_count: usize) -> TabularMdp {
+        TabularMdp {
+            state_count,
+            action_count,
+            transitions: vec![vec![vec![]; action_count]; state_count],
+            terminal: vec![false; state_count],
+        }
+    }
+
+    // add probability to reaching `next` from `state` with `action`,
+    // merged with an earlier transition to the same state and reward
+    pub fn add_transition(
+        &mut self,
+        state: usize,
+        action: usize,
+        next: usize,
+        probability: f64,
+        reward: f64,
+    ) {
+        if probability <= 0.0 {
+            return;
+        }
+        let transitions = &mut self.transitions[state][action];
+        match transitions
+            .iter_mut()
+            .find(|t| t.next == next && t.reward == reward)
+        {
+            Some(t) => t.probability += probability,
+            None => transitions.push(Transition {
+                next,
+                probability,
+                reward,
+            }),
+        }
+    }
+
+    // make `state` absorbing, dropping its transitions
+    pub fn set_terminal(&mut self, state: usize) {
+        self.terminal[state] = true;
+        for action in 0..self.action_count {
+            self.transitions[state][action] = vec![Transition {
+                next: state,
+                probability: 1.0,
+                reward: 0.0,
+            }];
+        }
+    }
+
+    // P(next | state, action)
+    pub fn probability(&self, state: usize, action: usize, next: usize) -> f64 {
+        self.transitions[state][action]
+            .iter()
+            .filter(|t| t.next == next)
+            .map(|t| t.probability)
+            .sum()
+    }
+
+    // E[r | state, action]
+    pub fn expected_reward(&self, state: usize, action: usize) -> f64 {
+        self.transitions[state][action]
+            .iter()
+            .map(|t| t.probability * t.reward)
+            .sum()
+    }
+
+    // P as [state][action][next]
+    pub fn dense_transitions(&self) -> Vec<Vec<Vec<f64>>> {
+        (0..self.state_count)
+            .map(|s| {
+                (0..self.action_count)
+                    .map(|a| {
+                        let mut row = vec![0.0; self.state_count];
+                        for t in self.transitions[s][a].iter() {
+                            row[t.next] += t.probability;
+                        }
+                        row
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // expected rewards as [state][action]
+    pub fn dense_rewards(&self) -> Vec<Vec<f64>> {
+        (0..self.state_count)
+            .map(|s| {
+                (0..self.action_count)
+                    .map(|a| self.expected_reward(s, a))
+                    .collect()
+            })
+            .collect()
+    }
+
+    // the first (state, action) whose probabilities don't sum to 1
+    pub fn find_unnormalized(&self, tolerance: f64) -> Option<(usize, usize)> {
+        (0..self.state_count)
+            .flat_map(|s| (0..self.action_count).map(move |a| (s, a)))
+            .find(|&(s, a)| {
+                let total: f64 = self.transitions[s][a].iter().map(|t| t.probability).sum();
+                (total - 1.0).abs() > tolerance
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mdp::tabular_mdp::*;
+
+    #[test]
+    fn test_tabular_mdp() {
+        // two states, action 0 stays, action 1 moves with probability 0.25
+        let mut mdp = TabularMdp::new(2, 2);
+        mdp.add_transition(0, 0, 0, 1.0, -1.0);
+        mdp.add_transition(0, 1, 1, 0.25, 5.0);
+        mdp.add_transition(0, 1, 0, 0.5, -1.0);
+        mdp.add_transition(0, 1, 0, 0.25, -1.0);
+        assert_eq!(mdp.find_unnormalized(1e-9), Some((1, 0)));
+        mdp.set_terminal(1);
+        assert_eq!(mdp.find_unnormalized(1e-9), None);
+
+        assert_eq!(mdp.transitions[0][1].len(), 2);
+        assert_eq!(mdp.probability(0, 1, 0), 0.75);
+        assert_eq!(mdp.expected_reward(0, 1), 0.5);
+        assert_eq!(mdp.dense_transitions()[1][0], vec![0.0, 1.0]);
+        assert_eq!(mdp.dense_rewards(), vec![vec![-1.0, 0.5], vec![0.0, 0.0]]);
+    }
+}
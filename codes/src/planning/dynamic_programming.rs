@@ -0,0 +1,194 @@
+use crate::mdp::tabular_mdp::TabularMdp;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlanningConfig {
+    // discount factor
+    pub gamma: f64,
+    // stop once no state value changes by more than this
+    pub tolerance: f64,
+    // give up after this many sweeps (value iteration and policy
+    // evaluation) or policy improvements (policy iteration)
+    pub max_iterations: usize,
+}
+
+impl Default for PlanningConfig {
+    fn default() -> PlanningConfig {
+        PlanningConfig {
+            gamma: 0.9,
+            tolerance: 1e-8,
+            max_iterations: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    // value of every state under `policy`
+    pub values: Vec<f64>,
+    // greedy action of every state
+    pub policy: Vec<usize>,
+    pub iterations: usize,
+    // false if `max_iterations` ran out first
+    pub converged: bool,
+}
+
+// one-step lookahead: expected return of each action in `state`
+// when the values of the next states are `values`
+pub fn q_values(mdp: &TabularMdp, values: &[f64], gamma: f64, state: usize) -> Vec<f64> {
+    (0..mdp.action_count)
+        .map(|a| {
+            mdp.transitions[state][a]
+                .iter()
+                .map(|t| t.probability * (t.reward + gamma * values[t.next]))
+                .sum()
+        })
+        .collect()
+}
+
+// the best action, keeping `current` on (near) ties
+// so policy iteration doesn't flip between equal actions
+fn greedy_action(q: &[f64], current: usize) -> usize {
+    let mut best = current;
+    for (a, &value) in q.iter().enumerate() {
+        if value > q[best] + 1e-12 {
+            best = a;
+        }
+    }
+    best
+}
+
+pub fn greedy_policy(mdp: &TabularMdp, values: &[f64], gamma: f64) -> Vec<usize> {
+    (0..mdp.state_count)
+        .map(|s| greedy_action(&q_values(mdp, values, gamma, s), 0))
+        .collect()
+}
+
+// V(s) = max_a sum_s' P(s'|s,a) (r + gamma * V(s'))
+pub fn value_iteration(mdp: &TabularMdp, config: &PlanningConfig) -> Solution {
+    let mut values = vec![0.0; mdp.state_count];
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < config.max_iterations {
+        iterations += 1;
+        let mut delta: f64 = 0.0;
+        for s in 0..mdp.state_count {
+            let best = q_values(mdp, &values, config.gamma, s)
+                .into_iter()
+                .fold(f64::NEG_INFINITY, f64::max);
+            delta = delta.max((best - values[s]).abs());
+            values[s] = best;
+        }
+        if delta <= config.tolerance {
+            converged = true;
+            break;
+        }
+    }
+
+    let policy = greedy_policy(mdp, &values, config.gamma);
+    Solution {
+        values,
+        policy,
+        iterations,
+        converged,
+    }
+}
+
+// values of following `policy`, by repeated sweeps from `values`;
+// returns whether the sweeps converged
+pub fn policy_evaluation(
+    mdp: &TabularMdp,
+    policy: &[usize],
+    values: &mut [f64],
+    config: &PlanningConfig,
+) -> bool {
+    for _ in 0..config.max_iterations {
+        let mut delta: f64 = 0.0;
+        for s in 0..mdp.state_count {
+            let value: f64 = mdp.transitions[s][policy[s]]
+                .iter()
+                .map(|t| t.probability * (t.reward + config.gamma * values[t.next]))
+                .sum();
+            delta = delta.max((value - values[s]).abs());
+            values[s] = value;
+        }
+        if delta <= config.tolerance {
+            return true;
+        }
+    }
+    false
+}
+
+// alternate evaluating the policy and making it greedy
+// until it stops changing
+pub fn policy_iteration(mdp: &TabularMdp, config: &PlanningConfig) -> Solution {
+    let mut values = vec![0.0; mdp.state_count];
+    let mut policy = vec![0; mdp.state_count];
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < config.max_iterations {
+        iterations += 1;
+        let evaluated = policy_evaluation(mdp, &policy, &mut values, config);
+
+        let mut stable = true;
+        for (s, action) in policy.iter_mut().enumerate() {
+            let greedy = greedy_action(&q_values(mdp, &values, config.gamma, s), *action);
+            if greedy != *action {
+                *action = greedy;
+                stable = false;
+            }
+        }
+        if stable {
+            converged = evaluated;
+            break;
+        }
+    }
+
+    Solution {
+        values,
+        policy,
+        iterations,
+        converged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::grid_world::GridWorld;
+    use crate::planning::dynamic_programming::*;
+
+    #[test]
+    fn test_static_grid_solutions() {
+        let grid_mdp = GridWorld::new(4, String::from("static")).to_mdp();
+        let config = PlanningConfig::default();
+        let by_values = value_iteration(&grid_mdp.mdp, &config);
+        let by_policy = policy_iteration(&grid_mdp.mdp, &config);
+        assert!(by_values.converged);
+        assert!(by_policy.converged);
+
+        // 6 steps of -1, then +10 for the goal on the 7th move
+        let expected = -(1.0 - 0.9f64.powi(6)) / 0.1 + 10.0 * 0.9f64.powi(6);
+        assert!((by_values.values[0] - expected).abs() < 1e-6);
+        for s in 0..grid_mdp.mdp.state_count {
+            assert!((by_values.values[s] - by_policy.values[s]).abs() < 1e-6);
+            if grid_mdp.mdp.terminal[s] {
+                assert_eq!(by_values.values[s], 0.0);
+            }
+        }
+
+        // both first go down, around the wall
+        assert_eq!(by_values.policy[0], 1);
+        assert_eq!(by_policy.policy[0], 1);
+    }
+
+    #[test]
+    fn test_iteration_limit() {
+        let grid_mdp = GridWorld::new(4, String::from("static")).to_mdp();
+        let config = PlanningConfig {
+            max_iterations: 2,
+            ..PlanningConfig::default()
+        };
+        let solution = value_iteration(&grid_mdp.mdp, &config);
+        assert!(!solution.converged);
+        assert_eq!(solution.iterations, 2);
+    }
+}
@@ -0,0 +1 @@
+pub mod dynamic_programming;
@@ -1,6 +1,6 @@
 use plotlib::page::Page;
 use plotlib::repr::Plot;
-use plotlib::style::{PointMarker, PointStyle};
+use plotlib::style::{LineStyle, PointMarker, PointStyle};
 use plotlib::view::ContinuousView;
 
 // plot scatter graph using xy data and save to disk
@@ -12,6 +12,7 @@ use plotlib::view::ContinuousView;
 // ymax: y-axe max value
 // xlabel: x-axe label
 // ylabel: y-axe label
+#[allow(clippy::too_many_arguments)]
 pub fn xy_scatter_plot(
 	path: String,
 	xy: Vec<(f64, f64)>,
@@ -38,3 +39,76 @@ pub fn xy_scatter_plot(
 
 	Page::single(&view).save(path).unwrap();
 }
+
+// plot training losses against update steps
+pub fn loss_plot(path: String, losses: &[f64]) {
+	let xy: Vec<(f64, f64)> = losses
+		.iter()
+		.enumerate()
+		.map(|(i, &loss)| (i as f64, loss))
+		.collect();
+	let ymin = losses.iter().copied().fold(f64::INFINITY, f64::min).min(0.0);
+	let ymax = losses.iter().copied().fold(0.0, f64::max);
+	xy_scatter_plot(
+		path,
+		xy,
+		0.0,
+		losses.len().max(1) as f64,
+		ymin,
+		ymax.max(1.0),
+		String::from("step"),
+		String::from("loss"),
+	);
+}
+
+// colours of successive series in a line plot
+const PALETTE: [&str; 6] = ["#DD3355", "#3355DD", "#33AA55", "#DD8833", "#8833DD", "#555555"];
+
+// plot several labelled series as lines on one graph and save to disk,
+// the axes cover all the data
+pub fn xy_line_plot(
+	path: String,
+	series: Vec<(String, Vec<(f64, f64)>)>,
+	xlabel: String,
+	ylabel: String,
+) {
+	let mut view = ContinuousView::new().x_label(xlabel).y_label(ylabel);
+	for (i, (label, xy)) in series.into_iter().enumerate() {
+		let plot: Plot = Plot::new(xy)
+			.line_style(LineStyle::new().colour(PALETTE[i % PALETTE.len()]))
+			.legend(label);
+		view = view.add(plot);
+	}
+
+	Page::single(&view).save(path).unwrap();
+}
+
+// (x, mean, half-width) points of a series with a confidence band
+pub type Band = Vec<(f64, f64, f64)>;
+
+// plot several labelled series of (x, mean, half-width) points, each
+// as a line between two thin lines marking mean -/+ half-width
+pub fn xy_band_plot(
+	path: String,
+	series: Vec<(String, Band)>,
+	xlabel: String,
+	ylabel: String,
+) {
+	let mut view = ContinuousView::new().x_label(xlabel).y_label(ylabel);
+	for (i, (label, points)) in series.into_iter().enumerate() {
+		let colour = PALETTE[i % PALETTE.len()];
+		let lower = points.iter().map(|&(x, y, h)| (x, y - h)).collect();
+		let upper = points.iter().map(|&(x, y, h)| (x, y + h)).collect();
+		let mean = points.iter().map(|&(x, y, _)| (x, y)).collect();
+		for bound in [lower, upper] {
+			let plot: Plot = Plot::new(bound).line_style(LineStyle::new().colour(colour).width(0.5));
+			view = view.add(plot);
+		}
+		let plot: Plot = Plot::new(mean)
+			.line_style(LineStyle::new().colour(colour))
+			.legend(label);
+		view = view.add(plot);
+	}
+
+	Page::single(&view).save(path).unwrap();
+}
\ No newline at end of file
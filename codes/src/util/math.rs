@@ -0,0 +1,22 @@
+// index of the largest value, the first one on ties
+pub fn argmax(values: &[f64]) -> usize {
+    assert!(!values.is_empty(), "argmax of no values");
+    let mut best = 0;
+    for (i, &value) in values.iter().enumerate() {
+        if value > values[best] {
+            best = i;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::util::math::*;
+
+    #[test]
+    fn test_argmax() {
+        assert_eq!(argmax(&[1.0, 3.0, 2.0, 3.0]), 1);
+        assert_eq!(argmax(&[-1.0]), 0);
+    }
+}